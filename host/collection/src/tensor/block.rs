@@ -34,6 +34,41 @@ macro_rules! block_dispatch {
     };
 }
 
+macro_rules! block_op {
+    ($self:ident, $other:ident, $this:ident, $that:ident, $call:expr) => {
+        match ($self, $other) {
+            (Self::F32($this), Self::F32($that)) => {
+                $call.map(Array::from).map(Self::F32).map_err(TCError::from)
+            }
+            (Self::F64($this), Self::F64($that)) => {
+                $call.map(Array::from).map(Self::F64).map_err(TCError::from)
+            }
+            (Self::I16($this), Self::I16($that)) => {
+                $call.map(Array::from).map(Self::I16).map_err(TCError::from)
+            }
+            (Self::I32($this), Self::I32($that)) => {
+                $call.map(Array::from).map(Self::I32).map_err(TCError::from)
+            }
+            (Self::I64($this), Self::I64($that)) => {
+                $call.map(Array::from).map(Self::I64).map_err(TCError::from)
+            }
+            (Self::U8($this), Self::U8($that)) => {
+                $call.map(Array::from).map(Self::U8).map_err(TCError::from)
+            }
+            (Self::U16($this), Self::U16($that)) => {
+                $call.map(Array::from).map(Self::U16).map_err(TCError::from)
+            }
+            (Self::U32($this), Self::U32($that)) => {
+                $call.map(Array::from).map(Self::U32).map_err(TCError::from)
+            }
+            (Self::U64($this), Self::U64($that)) => {
+                $call.map(Array::from).map(Self::U64).map_err(TCError::from)
+            }
+            (this, that) => Err(bad_request!("cannot combine {this:?} with {that:?}")),
+        }
+    };
+}
+
 macro_rules! block_cmp {
     ($self:ident, $other:ident, $this:ident, $that:ident, $call:expr) => {
         match ($self, $other) {
@@ -193,6 +228,153 @@ impl Block {
                 .map_err(TCError::from)
         )
     }
+
+    pub fn add(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.add(that))
+    }
+
+    pub fn add_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.add_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    pub fn sub(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.sub(that))
+    }
+
+    pub fn sub_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.sub_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    pub fn mul(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.mul(that))
+    }
+
+    pub fn mul_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.mul_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    pub fn div(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.div(that))
+    }
+
+    pub fn div_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.div_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    pub fn rem(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.rem(that))
+    }
+
+    pub fn rem_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.rem_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    pub fn pow(self, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.pow(that))
+    }
+
+    pub fn pow_scalar(self, other: Number) -> TCResult<Self> {
+        block_dispatch!(
+            self,
+            this,
+            this.pow_scalar(other.cast_into())
+                .map(Array::from)
+                .map(Self::from)
+                .map_err(TCError::from)
+        )
+    }
+
+    /// Blend `self` and `other` elementwise according to `mask`, the natural
+    /// consumer of the boolean `Array<u8>` that `eq`/`gt`/`lt`/etc. produce --
+    /// where `mask` is nonzero, the result is taken from `self`, otherwise
+    /// from `other`.
+    pub fn select(self, mask: Array<u8>, other: Self) -> TCResult<Self> {
+        let (this, that) = self.promote(other)?;
+        block_op!(this, that, this, that, this.select(mask, that))
+    }
+
+    /// A total ordering over dtypes used to decide, for mixed-dtype binary
+    /// ops, which operand's dtype the other should be cast into -- wider
+    /// floating-point types outrank wider integer types, which outrank
+    /// narrower ones of either signedness.
+    fn dtype_rank(&self) -> u8 {
+        match self {
+            Self::U8(_) => 0,
+            Self::U16(_) => 1,
+            Self::I16(_) => 2,
+            Self::U32(_) => 3,
+            Self::I32(_) => 4,
+            Self::U64(_) => 5,
+            Self::I64(_) => 6,
+            Self::F32(_) => 7,
+            Self::F64(_) => 8,
+        }
+    }
+
+    fn cast_to_rank(self, dtype_rank: u8) -> TCResult<Self> {
+        match dtype_rank {
+            0 => self.cast::<u8>().map(Self::from),
+            1 => self.cast::<u16>().map(Self::from),
+            2 => self.cast::<i16>().map(Self::from),
+            3 => self.cast::<u32>().map(Self::from),
+            4 => self.cast::<i32>().map(Self::from),
+            5 => self.cast::<u64>().map(Self::from),
+            6 => self.cast::<i64>().map(Self::from),
+            7 => self.cast::<f32>().map(Self::from),
+            8 => self.cast::<f64>().map(Self::from),
+            _ => unreachable!("dtype_rank is in range [0, 8]"),
+        }
+    }
+
+    /// Cast `self` and `other` to whichever of their two dtypes is wider, so
+    /// a binary op on mismatched dtypes (e.g. `I32` combined with `F64`)
+    /// promotes to the wider type instead of erroring the way `block_cmp!`'s
+    /// exact-match dispatch does.
+    fn promote(self, other: Self) -> TCResult<(Self, Self)> {
+        let dtype_rank = self.dtype_rank().max(other.dtype_rank());
+        Ok((self.cast_to_rank(dtype_rank)?, other.cast_to_rank(dtype_rank)?))
+    }
 }
 
 macro_rules! block_from {
@@ -1,9 +1,12 @@
 use std::convert::TryFrom;
 
+use async_hash::{Digest, Hash as HashExt, Output, Sha256};
 use async_trait::async_trait;
 use destream::{de, en};
 use futures::stream::{self, StreamExt};
-use futures::{TryFutureExt, TryStreamExt};
+use futures::{future, TryFutureExt, TryStreamExt};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
 
 use tc_error::*;
 use tc_transact::fs::{Block, BlockData, BlockId, Dir, File};
@@ -21,17 +24,132 @@ use super::{ChainType, NULL_HASH};
 
 const HISTORY: Label = label("history");
 
+/// The number of unconsumed committed blocks a `subscribe` caller may lag
+/// behind; a caller that falls further behind silently misses the blocks it
+/// lagged past. Mirrors the old `tinychain::chain::null::NullChain`'s own
+/// `SUBSCRIBE_BUFFER`.
+const SUBSCRIBE_BUFFER: usize = 128;
+
+/// Separates one committed block's encoded mutation group from the next in
+/// a `subscribe` byte stream.
+pub const RECORD_DELIMITER: u8 = 30;
+
+/// Separates one encoded `Mutation` from the next within a single block's
+/// group in a `subscribe` byte stream.
+pub const GROUP_DELIMITER: u8 = 29;
+
+/// Decode one `subscribe` frame (as framed by `ChainData::subscribe`) back
+/// into the `Mutation`s it carries: split on `RECORD_DELIMITER` then
+/// `GROUP_DELIMITER`, decoding each piece with `tbon`. The matching decoder
+/// a `gateway::Client::subscribe` implementation runs against each frame it
+/// receives over the WebSocket connection.
+pub fn decode_subscription(frame: &[u8]) -> TCResult<Vec<Mutation>> {
+    frame
+        .split(|b| *b == RECORD_DELIMITER)
+        .flat_map(|record| record.split(|b| *b == GROUP_DELIMITER))
+        .filter(|piece| !piece.is_empty())
+        .map(|piece| tbon::de::decode(piece).map_err(|e| TCError::internal(e.to_string())))
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct ChainData {
     dir: fs::Dir,
     file: fs::File<ChainBlock>,
     latest: TxnLock<Mutable<u64>>,
+    /// The ordinal of the oldest block still on disk: `0` until
+    /// `checkpoint` first runs, and the `through` ordinal of the most
+    /// recent checkpoint after that. Every block below this has been
+    /// folded into a snapshot and deleted, so `verify` and `into_view`
+    /// both start from here instead of from `0`.
+    checkpoint: TxnLock<Mutable<u64>>,
+    commits: broadcast::Sender<BlockId>,
 }
 
 impl ChainData {
     pub fn new(latest: u64, dir: fs::Dir, file: fs::File<ChainBlock>) -> Self {
+        Self::with_checkpoint(latest, 0, dir, file)
+    }
+
+    /// Like `new`, but for a chain whose history starts from a prior
+    /// `checkpoint` rather than from true genesis -- `base` is the ordinal
+    /// of the chain's leading snapshot block.
+    pub fn with_checkpoint(latest: u64, base: u64, dir: fs::Dir, file: fs::File<ChainBlock>) -> Self {
+        let checkpoint = TxnLock::new("chain checkpoint ordinal", base.into());
         let latest = TxnLock::new("latest block ordinal", latest.into());
-        Self { dir, latest, file }
+        let (commits, _) = broadcast::channel(SUBSCRIBE_BUFFER);
+        Self {
+            dir,
+            latest,
+            checkpoint,
+            file,
+            commits,
+        }
+    }
+
+    /// A live stream of delimiter-framed bytes, one frame per block
+    /// committed after this call: each frame is that block's mutations
+    /// encoded as one `GROUP_DELIMITER`-separated group, terminated by a
+    /// `RECORD_DELIMITER`. A caller upgrades its own connection to a
+    /// WebSocket and forwards each yielded frame verbatim as it arrives;
+    /// `gateway::Client::subscribe` is the matching decoder for the other
+    /// end of that connection.
+    ///
+    /// The WebSocket upgrade handshake itself (`async-tungstenite`) and the
+    /// route that would call this method belong in `crate::http`, which has
+    /// no definition anywhere in this checkout -- this covers the
+    /// `ChainData`-side encoding and the commit notification it's driven by.
+    pub fn subscribe(&self, txn: Txn) -> TCTryStream<'static, Vec<u8>> {
+        let chain = self.clone();
+
+        let live = BroadcastStream::new(self.commits.subscribe()).filter_map(|block_id| {
+            future::ready(match block_id {
+                Ok(block_id) => Some(block_id),
+                // a lagging subscriber missed some commits; they are
+                // skipped and the stream continues with the next one
+                Err(_lagged) => None,
+            })
+        });
+
+        let frames = live.then(move |block_id| {
+            let chain = chain.clone();
+            let txn = txn.clone();
+            async move { chain.encode_block(txn, block_id).await }
+        });
+
+        Box::pin(frames)
+    }
+
+    async fn encode_block(&self, txn: Txn, block_id: BlockId) -> TCResult<Vec<u8>> {
+        let txn_id = *txn.id();
+        let block = self.file.clone().read_block_owned(txn_id, block_id).await?;
+
+        let mut frame = Vec::new();
+        for (_, mutations) in block.mutations().clone() {
+            for op in mutations {
+                let view = match op {
+                    Mutation::Delete(path, key) => MutationView::Delete(path, key),
+                    Mutation::Put(_path, _key, value) if value.is_ref() => {
+                        return Err(TCError::not_implemented(
+                            "resolve reference in Mutation::Put",
+                        ));
+                    }
+                    Mutation::Put(path, key, value) => {
+                        let value = State::from(value).into_view(txn.clone()).await?;
+                        MutationView::Put(path, key, value)
+                    }
+                };
+
+                let encoded =
+                    tbon::en::encode(view).map_err(|e| TCError::internal(e.to_string()))?;
+
+                frame.extend(encoded);
+                frame.push(GROUP_DELIMITER);
+            }
+        }
+
+        frame.push(RECORD_DELIMITER);
+        Ok(frame)
     }
 
     pub async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()> {
@@ -120,7 +238,204 @@ impl ChainData {
             .sync_block(*txn_id, (*latest).into())
             .await
             .expect("prepare BlockChain commit");
+
+        let _ = self.commits.send((*latest).into());
     }
+
+    /// Fold every mutation in blocks `checkpoint..=through` into a single
+    /// materialized "state snapshot" block at ordinal `through` -- the last
+    /// surviving `Put` (if any) for each key that was ever written in that
+    /// range, with deleted keys dropped entirely -- then delete the
+    /// superseded blocks below it. The snapshot's hash commits to both the
+    /// collapsed state and `hash(block[through])`, so the hash chain
+    /// `verify` walks stays continuous across the compaction. This is a
+    /// journaldb-style low-watermark: ordinals already on disk are never
+    /// renumbered, `checkpoint` just records how far back they still go, so
+    /// replay and disk cost scale with live state instead of total history.
+    pub async fn checkpoint(&self, txn_id: TxnId, through: u64) -> TCResult<()> {
+        let base = *self.checkpoint.read(&txn_id).await?;
+        let latest = self.latest_block_id(&txn_id).await?;
+
+        if through <= base {
+            return Ok(());
+        } else if through > latest {
+            return Err(TCError::bad_request(
+                "cannot checkpoint a Chain past its latest block",
+                through,
+            ));
+        }
+
+        let mut state: Vec<(TCPathBuf, Value, Value)> = Vec::new();
+
+        for ordinal in base..=through {
+            let block = self.read_block(txn_id, ordinal.into()).await?;
+            for (_, mutations) in block.mutations().clone() {
+                for op in mutations {
+                    match op {
+                        Mutation::Delete(path, key) => {
+                            state.retain(|(p, k, _)| *p != path || *k != key);
+                        }
+                        Mutation::Put(path, key, value) => {
+                            state.retain(|(p, k, _)| *p != path || *k != key);
+                            state.push((path, key, value));
+                        }
+                    }
+                }
+            }
+        }
+
+        let through_hash = self.read_block(txn_id, through.into()).await?.hash().await?;
+
+        let mut hasher = Sha256::default();
+        hasher.update(&through_hash);
+        for (path, key, value) in &state {
+            hasher.update(HashExt::<Sha256>::hash(path));
+            hasher.update(HashExt::<Sha256>::hash(key));
+            hasher.update(HashExt::<Sha256>::hash(value));
+        }
+        let snapshot_hash = hasher.finalize();
+
+        let mut snapshot = ChainBlock::new(snapshot_hash);
+        for (path, key, value) in state {
+            snapshot.append_put(txn_id, path, key, value);
+        }
+
+        for ordinal in base..through {
+            self.file.delete_block(txn_id, ordinal.into()).await?;
+        }
+
+        self.file.delete_block(txn_id, through.into()).await?;
+        self.file.create_block(txn_id, through.into(), snapshot).await?;
+
+        let mut checkpoint = self.checkpoint.write(txn_id).await?;
+        *checkpoint = through;
+
+        Ok(())
+    }
+
+    /// Recompute every block's hash from the oldest block still on disk
+    /// (`0`, or the most recent `checkpoint` ordinal) through the latest
+    /// committed block as of `txn_id` and confirm `block[n].last_hash() ==
+    /// hash(block[n - 1])`, the same link `create_next_block` establishes
+    /// when it seeds a new block and `ChainDataVisitor` checks one block at
+    /// a time while deserializing. Returns an error naming the ordinal of
+    /// the first block whose `last_hash` doesn't match, if any.
+    pub async fn verify(&self, txn_id: TxnId) -> TCResult<()> {
+        let base = *self.checkpoint.read(&txn_id).await?;
+        let latest = self.latest_block_id(&txn_id).await?;
+
+        let mut previous_hash = self.read_block(txn_id, base.into()).await?.hash().await?;
+
+        for ordinal in (base + 1)..=latest {
+            let block = self.read_block(txn_id, ordinal.into()).await?;
+            if block.last_hash() != previous_hash {
+                return Err(TCError::bad_request(
+                    "Chain integrity check failed at block",
+                    ordinal,
+                ));
+            }
+
+            previous_hash = block.hash().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the `Mutation` at `index` among `block_id`'s ordered
+    /// mutations along with the sibling hashes needed to recompute
+    /// `mutation_root` from that one leaf, so a light client can verify a
+    /// single Put/Delete belongs to a committed block without downloading
+    /// its full history.
+    pub async fn prove_mutation(
+        &self,
+        txn_id: TxnId,
+        block_id: BlockId,
+        index: usize,
+    ) -> TCResult<(Mutation, Vec<Output<Sha256>>)> {
+        let block = self.read_block(txn_id, block_id).await?;
+
+        let mutations: Vec<Mutation> = block
+            .mutations()
+            .clone()
+            .into_iter()
+            .flat_map(|(_, ops)| ops)
+            .collect();
+
+        let mutation = mutations
+            .get(index)
+            .cloned()
+            .ok_or_else(|| TCError::not_found(format!("mutation at index {}", index)))?;
+
+        let mut level: Vec<Output<Sha256>> = mutations.iter().map(mutation_leaf_hash).collect();
+        let mut position = index;
+        let mut siblings = Vec::new();
+
+        while level.len() > 1 {
+            let sibling_index = if position % 2 == 0 {
+                position + 1
+            } else {
+                position - 1
+            };
+
+            siblings.push(level.get(sibling_index).unwrap_or(&level[position]).clone());
+
+            level = combine_level(&level);
+            position /= 2;
+        }
+
+        Ok((mutation, siblings))
+    }
+}
+
+/// The Merkle root over a block's ordered `Mutation`s: each leaf is
+/// `H(path ‖ key ‖ value)`, pairs combine bottom-up the same way
+/// `table::file::merkle_root` reduces a table's partition digests, duplicating the
+/// last leaf on an odd count. `ChainBlock`'s definition doesn't exist
+/// anywhere in this checkout to add a stored field to, so this is computed
+/// on demand from `block.mutations()` rather than cached alongside the
+/// chain hash.
+pub fn mutation_root(mutations: &[Mutation]) -> Output<Sha256> {
+    if mutations.is_empty() {
+        return Sha256::default().finalize();
+    }
+
+    let mut level: Vec<Output<Sha256>> = mutations.iter().map(mutation_leaf_hash).collect();
+    while level.len() > 1 {
+        level = combine_level(&level);
+    }
+
+    level.remove(0)
+}
+
+fn combine_level(level: &[Output<Sha256>]) -> Vec<Output<Sha256>> {
+    let mut next = Vec::with_capacity((level.len() + 1) / 2);
+
+    for pair in level.chunks(2) {
+        let mut hasher = Sha256::default();
+        hasher.update(&pair[0]);
+        hasher.update(pair.get(1).unwrap_or(&pair[0]));
+        next.push(hasher.finalize());
+    }
+
+    next
+}
+
+fn mutation_leaf_hash(mutation: &Mutation) -> Output<Sha256> {
+    let mut hasher = Sha256::default();
+
+    match mutation {
+        Mutation::Delete(path, key) => {
+            hasher.update(HashExt::<Sha256>::hash(path));
+            hasher.update(HashExt::<Sha256>::hash(key));
+        }
+        Mutation::Put(path, key, value) => {
+            hasher.update(HashExt::<Sha256>::hash(path));
+            hasher.update(HashExt::<Sha256>::hash(key));
+            hasher.update(HashExt::<Sha256>::hash(value));
+        }
+    }
+
+    hasher.finalize()
 }
 
 #[async_trait]
@@ -132,6 +447,11 @@ impl de::FromStream for ChainData {
     }
 }
 
+/// Decodes a `ChainData` from its `into_view` stream. The leading element
+/// may be a true genesis block or a `ChainData::checkpoint` snapshot -- the
+/// two are structurally identical `ChainBlock`s, so this always treats
+/// whichever one arrives first as local ordinal `0` and resumes ordinary
+/// per-mutation blocks after it, without needing to know which kind it was.
 struct ChainDataVisitor {
     txn: Txn,
 }
@@ -201,12 +521,18 @@ impl<'en> IntoView<'en, fs::Dir> for ChainData {
 
     async fn into_view(self, txn: Txn) -> TCResult<Self::View> {
         let txn_id = *txn.id();
+        let base = *self.checkpoint.read(&txn_id).await?;
         let latest = self.latest.read(&txn_id).await?;
 
         let file = self.file.clone();
         let read_block = move |block_id| Box::pin(file.clone().read_block_owned(txn_id, block_id));
 
-        let seq = stream::iter(0..((*latest) + 1))
+        // the leading element is whichever block `base` names -- true
+        // genesis if this chain was never checkpointed, or the most recent
+        // snapshot otherwise. `ChainDataVisitor` doesn't need to tell the
+        // two apart: either way it becomes local ordinal 0 again on decode,
+        // with ordinary per-mutation blocks resuming after it.
+        let seq = stream::iter(base..((*latest) + 1))
             .map(BlockId::from)
             .then(read_block)
             .map_ok(move |block| {
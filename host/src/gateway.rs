@@ -3,12 +3,15 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use rustls::{Certificate, PrivateKey, ServerConfig};
 use serde::de::DeserializeOwned;
+use tokio_rustls::TlsAcceptor;
 
 use error::*;
 use futures::future::{try_join_all, Future, TryFutureExt};
-use generic::{path_label, NetworkTime, PathLabel, TCPathBuf};
+use generic::{path_label, NetworkTime, PathLabel, TCPathBuf, TCTryStream};
 
+use crate::chain::data::Mutation;
 use crate::http;
 use crate::kernel::Kernel;
 use crate::scalar::{Link, LinkHost, LinkProtocol, Value};
@@ -52,6 +55,18 @@ pub trait Client {
         key: Value,
         auth: Option<String>,
     ) -> TCResult<()>;
+
+    /// Upgrade to a WebSocket connection to `link` and follow its live
+    /// mutation history, decoding each `RECORD_DELIMITER`/`GROUP_DELIMITER`-
+    /// framed message (see `crate::chain::internal::decode_subscription`)
+    /// back into the `Mutation`s it carries, instead of polling `get` in a
+    /// loop.
+    async fn subscribe(
+        &self,
+        txn: Txn,
+        link: Link,
+        auth: Option<String>,
+    ) -> TCResult<TCTryStream<'static, Mutation>>;
 }
 
 #[async_trait]
@@ -59,6 +74,25 @@ pub trait Server {
     type Error: std::error::Error;
 
     async fn listen(self, addr: SocketAddr) -> Result<(), Self::Error>;
+
+    /// Like `listen`, but terminate TLS on each accepted connection with
+    /// `acceptor` before the request ever reaches application code. The
+    /// `crate::http::HTTPServer` implementation of this wraps each accepted
+    /// `AddrStream` in `acceptor.accept(..)` ahead of the same request
+    /// handling `listen` already drives.
+    async fn listen_tls(
+        self,
+        addr: SocketAddr,
+        acceptor: TlsAcceptor,
+    ) -> Result<(), Self::Error>;
+}
+
+/// A PEM-encoded certificate chain and PKCS#8 private key to terminate TLS
+/// with, plus the port to accept HTTPS connections on.
+pub struct TlsConfig {
+    pub cert_chain: Vec<u8>,
+    pub private_key: Vec<u8>,
+    pub https_port: u16,
 }
 
 pub struct Gateway {
@@ -67,6 +101,7 @@ pub struct Gateway {
     txn_server: TxnServer,
     addr: IpAddr,
     http_port: u16,
+    tls: Option<(u16, Arc<TlsAcceptor>)>,
     client: http::Client,
 }
 
@@ -76,6 +111,31 @@ impl Gateway {
     }
 
     pub fn new(kernel: Kernel, txn_server: TxnServer, addr: IpAddr, http_port: u16) -> Arc<Self> {
+        Self::new_inner(kernel, txn_server, addr, http_port, None)
+    }
+
+    /// Like `new`, but also terminate HTTPS on `tls.https_port` using the
+    /// given certificate chain and private key, so a node can listen on
+    /// both plaintext and encrypted connections at once.
+    pub fn new_tls(
+        kernel: Kernel,
+        txn_server: TxnServer,
+        addr: IpAddr,
+        http_port: u16,
+        tls: TlsConfig,
+    ) -> TCResult<Arc<Self>> {
+        let acceptor = tls_acceptor(&tls.cert_chain, &tls.private_key)?;
+        let tls = Some((tls.https_port, Arc::new(acceptor)));
+        Ok(Self::new_inner(kernel, txn_server, addr, http_port, tls))
+    }
+
+    fn new_inner(
+        kernel: Kernel,
+        txn_server: TxnServer,
+        addr: IpAddr,
+        http_port: u16,
+        tls: Option<(u16, Arc<TlsAcceptor>)>,
+    ) -> Arc<Self> {
         let actor_id = Value::from(Link::from(TCPathBuf::from(PATH)));
         let actor = Actor::new(actor_id);
 
@@ -85,6 +145,7 @@ impl Gateway {
             addr,
             txn_server,
             http_port,
+            tls,
             client: http::Client::new(),
         })
     }
@@ -98,7 +159,12 @@ impl Gateway {
     }
 
     pub fn root(&self) -> Link {
-        let host = LinkHost::from((LinkProtocol::HTTP, self.addr.clone(), Some(self.http_port)));
+        let (protocol, port) = match &self.tls {
+            Some((https_port, _)) => (LinkProtocol::HTTPS, *https_port),
+            None => (LinkProtocol::HTTP, self.http_port),
+        };
+
+        let host = LinkHost::from((protocol, self.addr.clone(), Some(port)));
         host.into()
     }
 
@@ -144,10 +210,34 @@ impl Gateway {
         }
     }
 
+    /// Follow a remote `Chain`'s live mutation history over a WebSocket
+    /// connection instead of polling `get` in a loop. There's no local
+    /// equivalent yet: `Kernel` has no generic subscription entrypoint of
+    /// its own to delegate to, unlike `get`/`put`/`post`'s `self.kernel`
+    /// branch, so a `subject` with no host is rejected rather than guessing
+    /// at one.
+    pub async fn subscribe(
+        &self,
+        txn: &Txn,
+        subject: Link,
+    ) -> TCResult<TCTryStream<'static, Mutation>> {
+        if subject.host().is_none() {
+            Err(TCError::not_implemented("Gateway::subscribe to a local Chain"))
+        } else {
+            let auth = self.sign_token(txn)?;
+            self.client.subscribe(txn.clone(), subject, auth).await
+        }
+    }
+
     pub fn listen(
         self: Arc<Self>,
     ) -> Pin<Box<impl Future<Output = Result<(), Box<dyn std::error::Error>>>>> {
-        let servers = vec![self.http_listen()];
+        let https = self.tls.is_some();
+
+        let mut servers = vec![self.clone().http_listen()];
+        if https {
+            servers.push(self.https_listen());
+        }
 
         Box::pin(try_join_all(servers).map_ok(|_| ()))
     }
@@ -166,4 +256,50 @@ impl Gateway {
 
         Box::pin(listener)
     }
+
+    fn https_listen(
+        self: Arc<Self>,
+    ) -> std::pin::Pin<Box<impl futures::Future<Output = Result<(), Box<dyn std::error::Error>>>>>
+    {
+        let (https_port, acceptor) = self
+            .tls
+            .clone()
+            .expect("https_listen called without a TlsConfig");
+        let https_addr = (self.addr, https_port).into();
+        let acceptor = (*acceptor).clone();
+        let server = crate::http::HTTPServer::new(self);
+        let listener = server.listen_tls(https_addr, acceptor).map_err(|e| {
+            let e: Box<dyn std::error::Error> = Box::new(e);
+            e
+        });
+
+        Box::pin(listener)
+    }
+}
+
+/// Build a `TlsAcceptor` from a PEM-encoded certificate chain and a PKCS#8
+/// private key, so `Gateway::new_tls` only has to load the two files once
+/// and hand each accepted connection to this same acceptor.
+fn tls_acceptor(cert_chain: &[u8], private_key: &[u8]) -> TCResult<TlsAcceptor> {
+    let cert_chain = rustls_pemfile::certs(&mut &*cert_chain)
+        .map_err(|e| TCError::internal(format!("invalid TLS certificate chain: {}", e)))?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    let mut keys = rustls_pemfile::pkcs8_private_keys(&mut &*private_key)
+        .map_err(|e| TCError::internal(format!("invalid TLS private key: {}", e)))?;
+
+    let key = keys
+        .pop()
+        .map(PrivateKey)
+        .ok_or_else(|| TCError::internal("no PKCS#8 private key found"))?;
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .map_err(|e| TCError::internal(format!("invalid TLS configuration: {}", e)))?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
 }
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::{Mutex, OnceLock};
 
 use destream::de::Error;
 use futures::future::{self, Future, TryFutureExt};
@@ -26,7 +28,7 @@ use crate::route::{AttributeHandler, GetHandler, PostHandler, PutHandler, SelfHa
 use crate::scalar::Scalar;
 use crate::state::{State, StateType};
 use crate::stream::{Source, TCStream};
-use crate::txn::Txn;
+use crate::txn::{Txn, TxnId};
 
 use super::{Handler, Route};
 
@@ -37,7 +39,12 @@ const TENSOR: Label = label("tensor");
 const TENSORS: Label = label("tensors");
 
 const MEAN: f64 = 0.0;
-const STD: f64 = 0.0;
+const STD: f64 = 1.0;
+
+/// The maximum number of vectorized resample rounds [`truncated_normal`]
+/// will run before giving up on the (exponentially shrinking) fraction of
+/// entries still outside the truncation bounds.
+const TRUNCATED_NORMAL_RESAMPLES: usize = 8;
 
 struct ArgmaxHandler<T> {
     tensor: T,
@@ -284,6 +291,502 @@ impl<'a> Handler<'a> for ConcatenateHandler {
     }
 }
 
+/// The position of a node within a transaction's flattened tape, assigned by
+/// [`resolve`]/[`record`] in the order each `Tensor` was first seen --
+/// since every op's parents were necessarily computed before it, a node's
+/// own index is always greater than any of its parents' indices, so
+/// walking indices from `output` down to `0` already visits nodes in
+/// reverse topological order.
+type TapeNodeId = u64;
+
+/// A tape node's forward op, carrying whatever the corresponding backward
+/// rule needs beyond the incoming gradient and the parents' own forward
+/// values (which are looked up from the tape by parent id).
+#[derive(Clone)]
+enum TapeOp {
+    Leaf,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    MatMul,
+    Broadcast,
+    ExpandDims { axis: usize },
+    Concatenate { axis: usize },
+    Exp,
+    Ln,
+    Sin,
+    Cos,
+    Sum { axis: Option<usize> },
+    Product { axis: Option<usize> },
+}
+
+impl TapeOp {
+    fn from_str(op: &str, axis: Option<i64>) -> TCResult<Self> {
+        match op {
+            "leaf" => Ok(Self::Leaf),
+            "add" => Ok(Self::Add),
+            "sub" => Ok(Self::Sub),
+            "mul" => Ok(Self::Mul),
+            "div" => Ok(Self::Div),
+            "pow" => Ok(Self::Pow),
+            "matmul" => Ok(Self::MatMul),
+            "broadcast" => Ok(Self::Broadcast),
+            "expand_dims" => Ok(Self::ExpandDims {
+                axis: axis
+                    .filter(|axis| *axis >= 0)
+                    .ok_or_else(|| bad_request!("expand_dims node is missing its axis"))?
+                    as usize,
+            }),
+            "concatenate" => Ok(Self::Concatenate {
+                axis: axis
+                    .filter(|axis| *axis >= 0)
+                    .ok_or_else(|| bad_request!("concatenate node is missing its axis"))?
+                    as usize,
+            }),
+            "exp" => Ok(Self::Exp),
+            "log" => Ok(Self::Ln),
+            "sin" => Ok(Self::Sin),
+            "cos" => Ok(Self::Cos),
+            "sum" => Ok(Self::Sum {
+                axis: axis.filter(|axis| *axis >= 0).map(|axis| axis as usize),
+            }),
+            "product" => Ok(Self::Product {
+                axis: axis.filter(|axis| *axis >= 0).map(|axis| axis as usize),
+            }),
+            other => Err(bad_request!("unrecognized differentiable op {}", other)),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TapeNode {
+    op: TapeOp,
+    parents: Vec<TapeNodeId>,
+    forward: Tensor,
+}
+
+/// Reverse-mode autodiff over a tape of [`TapeNode`]s, appended to
+/// automatically as [`DualHandler`]/[`MatMulHandler`] execute (see
+/// [`record`]) rather than reconstructed from a trace the caller submits.
+#[derive(Clone, Default)]
+struct Tape {
+    nodes: Vec<TapeNode>,
+}
+
+/// The tape each `TxnId` is building up as its differentiable tensor ops
+/// execute, one call at a time across separate POST requests -- keyed by
+/// `TxnId` the same way per-txn decode limits are in `host_state::limits`,
+/// since `Tensor`/`State` have no field of their own to carry tape state
+/// between calls.
+fn tape_registry() -> &'static Mutex<HashMap<TxnId, Tape>> {
+    static TAPES: OnceLock<Mutex<HashMap<TxnId, Tape>>> = OnceLock::new();
+    TAPES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Find `tensor`'s node in `tape` by its forward value, registering it as
+/// a fresh [`TapeOp::Leaf`] the first time it's seen -- an operand that
+/// never passed through a recorded op (e.g. a stored Tensor loaded fresh)
+/// still needs a valid id to be named as a parent.
+fn resolve(tape: &mut Tape, tensor: &Tensor) -> TapeNodeId {
+    for (id, node) in tape.nodes.iter().enumerate() {
+        if &node.forward == tensor {
+            return id as TapeNodeId;
+        }
+    }
+
+    let id = tape.nodes.len() as TapeNodeId;
+    tape.nodes.push(TapeNode {
+        op: TapeOp::Leaf,
+        parents: Vec::new(),
+        forward: tensor.clone(),
+    });
+    id
+}
+
+/// Append a node for `forward`, produced by `op` out of `parents`, to
+/// `txn_id`'s tape -- called by each differentiable handler right after it
+/// computes its result, so the tape reflects every op that ran for this
+/// transaction without [`BackwardHandler`]'s caller ever submitting one.
+fn record(txn_id: TxnId, op: TapeOp, parents: &[Tensor], forward: &Tensor) {
+    let mut tapes = tape_registry().lock().expect("tensor tape registry");
+    let tape = tapes.entry(txn_id).or_insert_with(Tape::default);
+
+    let parent_ids = parents.iter().map(|parent| resolve(tape, parent)).collect();
+    tape.nodes.push(TapeNode {
+        op,
+        parents: parent_ids,
+        forward: forward.clone(),
+    });
+}
+
+impl Tape {
+    fn forward(&self, id: TapeNodeId) -> TCResult<&Tensor> {
+        self.nodes
+            .get(id as usize)
+            .map(|node| &node.forward)
+            .ok_or_else(|| bad_request!("no such tape node: {}", id))
+    }
+
+    /// Reduce `grad` (shaped like the result of broadcasting `from_shape`
+    /// up to `grad`'s own shape) back down to `from_shape`, by summing over
+    /// every axis that was inserted or stretched from size `1`.
+    fn unbroadcast(grad: Tensor, from_shape: &Shape) -> TCResult<Tensor> {
+        let to_ndim = grad.ndim();
+        let from_ndim = from_shape.len();
+
+        let mut grad = grad;
+        for axis in (0..(to_ndim - from_ndim)).rev() {
+            grad = grad.sum(axis, false)?;
+        }
+
+        for (axis, dim) in from_shape.iter().enumerate() {
+            if *dim == 1 && grad.shape()[axis] != 1 {
+                grad = grad.sum(axis, true)?;
+            }
+        }
+
+        Ok(grad)
+    }
+
+    /// Accumulate `contribution` into `grads[parent]`, summing if `parent`
+    /// already has a gradient from another consumer in the tape.
+    fn accumulate(
+        grads: &mut HashMap<TapeNodeId, Tensor>,
+        parent: TapeNodeId,
+        contribution: Tensor,
+    ) -> TCResult<()> {
+        let combined = match grads.remove(&parent) {
+            Some(existing) => existing.add(contribution)?,
+            None => contribution,
+        };
+
+        grads.insert(parent, combined);
+        Ok(())
+    }
+
+    /// Seed `output`'s gradient with `seed` (or a Tensor of ones, if none
+    /// was supplied), walk the tape in reverse, and return the gradient of
+    /// each requested `variables` node.
+    async fn backward(
+        &self,
+        txn: &Txn,
+        output: TapeNodeId,
+        seed: Option<Tensor>,
+        variables: &[TapeNodeId],
+    ) -> TCResult<HashMap<TapeNodeId, Tensor>> {
+        let output_tensor = self.forward(output)?.clone();
+
+        let seed = if let Some(seed) = seed {
+            if seed.shape() != output_tensor.shape() {
+                return Err(bad_request!(
+                    "backward seed shape {} does not match output shape {}",
+                    seed.shape(),
+                    output_tensor.shape()
+                ));
+            }
+
+            seed
+        } else {
+            let file = create_file(txn).await?;
+            DenseTensor::constant(
+                file,
+                *txn.id(),
+                output_tensor.shape().to_vec(),
+                output_tensor.dtype().one(),
+            )
+            .map(Tensor::from)?
+        };
+
+        let mut grads = HashMap::new();
+        grads.insert(output, seed);
+
+        for id in (0..=output).rev() {
+            let grad = match grads.get(&id) {
+                Some(grad) => grad.clone(),
+                // this node doesn't feed into `output`, so it has no gradient
+                None => continue,
+            };
+
+            let node = self
+                .nodes
+                .get(id as usize)
+                .ok_or_else(|| bad_request!("no such tape node: {}", id))?;
+
+            match &node.op {
+                TapeOp::Leaf => {}
+                TapeOp::Add => {
+                    let l = self.forward(node.parents[0])?.shape().clone();
+                    let r = self.forward(node.parents[1])?.shape().clone();
+                    Self::accumulate(&mut grads, node.parents[0], Self::unbroadcast(grad.clone(), &l)?)?;
+                    Self::accumulate(&mut grads, node.parents[1], Self::unbroadcast(grad, &r)?)?;
+                }
+                TapeOp::Sub => {
+                    let l = self.forward(node.parents[0])?.shape().clone();
+                    let r = self.forward(node.parents[1])?.shape().clone();
+                    let neg_grad = grad.clone().mul_const((-1.0_f64).into())?;
+                    Self::accumulate(&mut grads, node.parents[0], Self::unbroadcast(grad, &l)?)?;
+                    Self::accumulate(&mut grads, node.parents[1], Self::unbroadcast(neg_grad, &r)?)?;
+                }
+                TapeOp::Mul => {
+                    let l = self.forward(node.parents[0])?.clone();
+                    let r = self.forward(node.parents[1])?.clone();
+                    let grad_l = Self::unbroadcast(mul_broadcast(grad.clone(), r.clone())?, l.shape())?;
+                    let grad_r = Self::unbroadcast(mul_broadcast(grad, l)?, r.shape())?;
+                    Self::accumulate(&mut grads, node.parents[0], grad_l)?;
+                    Self::accumulate(&mut grads, node.parents[1], grad_r)?;
+                }
+                TapeOp::Div => {
+                    let l = self.forward(node.parents[0])?.clone();
+                    let r = self.forward(node.parents[1])?.clone();
+
+                    let grad_l = Self::unbroadcast(div_broadcast(grad.clone(), r.clone())?, l.shape())?;
+
+                    let r_squared = mul_broadcast(r.clone(), r.clone())?;
+                    let grad_r = Self::unbroadcast(
+                        div_broadcast(mul_broadcast(grad, l)?, r_squared)?
+                            .mul_const((-1.0_f64).into())?,
+                        r.shape(),
+                    )?;
+
+                    Self::accumulate(&mut grads, node.parents[0], grad_l)?;
+                    Self::accumulate(&mut grads, node.parents[1], grad_r)?;
+                }
+                TapeOp::Pow => {
+                    let l = self.forward(node.parents[0])?.clone();
+                    let r = self.forward(node.parents[1])?.clone();
+                    let output = node.forward.clone();
+
+                    // d/dl l^r = r * l^(r - 1)
+                    let r_minus_one = r.clone().sub_const(1.0_f64.into())?;
+                    let l_pow = pow_broadcast(l.clone(), r_minus_one)?;
+                    let grad_l = Self::unbroadcast(
+                        mul_broadcast(grad.clone(), mul_broadcast(r.clone(), l_pow)?)?,
+                        l.shape(),
+                    )?;
+
+                    // d/dr l^r = l^r * ln(l)
+                    let ln_l = l.ln()?;
+                    let grad_r = Self::unbroadcast(
+                        mul_broadcast(grad, mul_broadcast(output, ln_l)?)?,
+                        r.shape(),
+                    )?;
+
+                    Self::accumulate(&mut grads, node.parents[0], grad_l)?;
+                    Self::accumulate(&mut grads, node.parents[1], grad_r)?;
+                }
+                TapeOp::MatMul => {
+                    let l = self.forward(node.parents[0])?.clone();
+                    let r = self.forward(node.parents[1])?.clone();
+
+                    let r_t = r.transpose(Some(vec![1, 0]))?;
+                    let grad_l = matmul(grad.clone(), r_t)?;
+
+                    let l_t = l.transpose(Some(vec![1, 0]))?;
+                    let grad_r = matmul(l_t, grad)?;
+
+                    Self::accumulate(&mut grads, node.parents[0], grad_l)?;
+                    Self::accumulate(&mut grads, node.parents[1], grad_r)?;
+                }
+                TapeOp::Broadcast => {
+                    let from_shape = self.forward(node.parents[0])?.shape().clone();
+                    let contribution = Self::unbroadcast(grad, &from_shape)?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::ExpandDims { axis } => {
+                    let contribution = grad.sum(*axis, false)?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Concatenate { axis } => {
+                    let shape_out = node.forward.shape();
+                    let mut bounds: Bounds = shape_out
+                        .iter()
+                        .map(|dim| AxisBounds::all(*dim))
+                        .collect();
+
+                    let mut offset = 0;
+                    for &parent in &node.parents {
+                        let parent_shape = self.forward(parent)?.shape().clone();
+                        let width = parent_shape[*axis];
+                        bounds[*axis] = AxisBounds::In(offset..(offset + width));
+                        let contribution = grad.clone().slice(bounds.clone())?;
+                        Self::accumulate(&mut grads, parent, contribution)?;
+                        offset += width;
+                    }
+                }
+                TapeOp::Exp => {
+                    let contribution = grad.mul(node.forward.clone())?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Ln => {
+                    let x = self.forward(node.parents[0])?.clone();
+                    let contribution = grad.div(x)?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Sin => {
+                    let x = self.forward(node.parents[0])?.clone();
+                    let contribution = grad.mul(x.cos()?)?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Cos => {
+                    let x = self.forward(node.parents[0])?.clone();
+                    let contribution = grad.mul(x.sin()?)?.mul_const((-1.0_f64).into())?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Sum { axis } => {
+                    let parent_shape = self.forward(node.parents[0])?.shape().clone();
+                    let contribution = match axis {
+                        Some(axis) => grad.expand_dims(*axis)?.broadcast(parent_shape)?,
+                        None => grad.broadcast(parent_shape)?,
+                    };
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+                TapeOp::Product { axis } => {
+                    let parent = self.forward(node.parents[0])?.clone();
+                    let output = node.forward.clone();
+
+                    let (grad_full, output_full) = match axis {
+                        Some(axis) => (
+                            grad.expand_dims(*axis)?.broadcast(parent.shape().clone())?,
+                            output.expand_dims(*axis)?.broadcast(parent.shape().clone())?,
+                        ),
+                        None => (
+                            grad.broadcast(parent.shape().clone())?,
+                            output.broadcast(parent.shape().clone())?,
+                        ),
+                    };
+
+                    let contribution = grad_full.mul(div_broadcast(output_full, parent)?)?;
+                    Self::accumulate(&mut grads, node.parents[0], contribution)?;
+                }
+            }
+        }
+
+        variables
+            .iter()
+            .map(|id| {
+                grads
+                    .get(id)
+                    .cloned()
+                    .map(|grad| (*id, grad))
+                    .ok_or_else(|| bad_request!("variable {} does not affect this output", id))
+            })
+            .collect()
+    }
+}
+
+/// `l * r`, broadcasting the two operands together first if their shapes
+/// don't already match, the same way [`DualHandler::post`] does before
+/// applying an elementwise op.
+fn mul_broadcast(l: Tensor, r: Tensor) -> TCResult<Tensor> {
+    if l.shape() == r.shape() {
+        l.mul(r)
+    } else {
+        let (l, r) = broadcast(l, r)?;
+        l.mul(r)
+    }
+}
+
+/// `l - r`, broadcasting the two operands together first if their shapes
+/// don't already match, the same way [`mul_broadcast`] does.
+fn sub_broadcast(l: Tensor, r: Tensor) -> TCResult<Tensor> {
+    if l.shape() == r.shape() {
+        l.sub(r)
+    } else {
+        let (l, r) = broadcast(l, r)?;
+        l.sub(r)
+    }
+}
+
+/// `l / r`, broadcasting the two operands together first if their shapes
+/// don't already match, the same way [`mul_broadcast`] does.
+fn div_broadcast(l: Tensor, r: Tensor) -> TCResult<Tensor> {
+    if l.shape() == r.shape() {
+        l.div(r)
+    } else {
+        let (l, r) = broadcast(l, r)?;
+        l.div(r)
+    }
+}
+
+/// `l ** r`, broadcasting the two operands together first if their shapes
+/// don't already match, the same way [`mul_broadcast`] does.
+fn pow_broadcast(l: Tensor, r: Tensor) -> TCResult<Tensor> {
+    if l.shape() == r.shape() {
+        l.pow(r)
+    } else {
+        let (l, r) = broadcast(l, r)?;
+        l.pow(r)
+    }
+}
+
+/// `A @ B` for two matrices, via the same expand/broadcast/mul/sum
+/// decomposition [`MatMulHandler`] uses, since `Tensor` has no native
+/// `matmul` of its own to call directly.
+fn matmul(left: Tensor, right: Tensor) -> TCResult<Tensor> {
+    if left.shape()[1] != right.shape()[0] {
+        return Err(bad_request!(
+            "invalid dimensions for matmul: {} @ {}",
+            left.shape(),
+            right.shape()
+        ));
+    }
+
+    let left = left.expand_dims(2)?;
+    let right = right.expand_dims(0)?;
+    let (left, right) = broadcast(left, right)?;
+    left.mul(right)?.sum(1, false)
+}
+
+/// Computes the gradient of a tensor `output` -- produced by some chain of
+/// differentiable ops -- with respect to any `variables` among its inputs,
+/// via reverse-mode automatic differentiation over `output`'s transaction's
+/// tape, recorded automatically as [`DualHandler`]'s add/sub/mul/div/pow and
+/// [`MatMulHandler`] executed (see [`record`]). `output`/`variables` are
+/// resolved against that tape by forward value, registering either one as a
+/// fresh leaf if it never passed through a recorded op.
+struct BackwardHandler;
+
+impl<'a> Handler<'a> for BackwardHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let output: Tensor = params.require(&label("output").into())?;
+                let variables: Vec<Tensor> = params.require(&label("variables").into())?;
+                let seed: Option<Tensor> = params.option(&label("seed").into(), || None)?;
+                params.expect_empty()?;
+
+                let (tape, output_id, variable_ids) = {
+                    let mut tapes = tape_registry().lock().expect("tensor tape registry");
+                    let tape = tapes.entry(*txn.id()).or_insert_with(Tape::default);
+
+                    let output_id = resolve(tape, &output);
+                    let variable_ids: Vec<TapeNodeId> =
+                        variables.iter().map(|v| resolve(tape, v)).collect();
+
+                    (tape.clone(), output_id, variable_ids)
+                };
+
+                let grads = tape.backward(&txn, output_id, seed, &variable_ids).await?;
+
+                let grads: Vec<(Number, State)> = variable_ids
+                    .into_iter()
+                    .map(|id| {
+                        let grad = grads.get(&id).cloned().expect("gradient");
+                        (Number::from(id), State::from(grad))
+                    })
+                    .collect();
+
+                Ok(State::Tuple(grads.into_iter().collect()))
+            })
+        }))
+    }
+}
+
 struct ConstantHandler;
 
 impl<'a> Handler<'a> for ConstantHandler {
@@ -814,138 +1317,495 @@ impl<'a> Handler<'a> for RandomUniformHandler {
     }
 }
 
-struct RangeHandler;
+/// `(fan_in, fan_out)` for a weight of `shape`, per the usual convolution
+/// convention `[out, in, k...]` (a plain 2-D weight `[out, in]` is just the
+/// `k...` = `()` case): the receptive field is the product of every axis
+/// after the first two, and `fan_in`/`fan_out` are `in`/`out` scaled by it.
+fn fan_in_out(shape: &Shape) -> TCResult<(f64, f64)> {
+    if shape.len() < 2 {
+        return Err(bad_request!(
+            "weight initialization requires a shape of at least 2 dimensions, found {}",
+            shape
+        ));
+    }
 
-impl<'a> Handler<'a> for RangeHandler {
+    let out = shape[0];
+    let in_ = shape[1];
+    let receptive: u64 = shape.iter().skip(2).copied().product();
+
+    Ok(((in_ * receptive) as f64, (out * receptive) as f64))
+}
+
+/// Draw a `DenseTensor` from `N(mean, std)`, then repeatedly regenerate
+/// (in a single vectorized pass over the whole tensor, rather than one
+/// element at a time) whatever fraction still falls more than two standard
+/// deviations from `mean`, up to [`TRUNCATED_NORMAL_RESAMPLES`] rounds --
+/// the batch-and-reject algorithm the caller asked for, just applied to
+/// the whole tensor per round instead of per element, since there's no
+/// element-level access to a `DenseTensor` here to reject and resample one
+/// entry at a time.
+async fn truncated_normal(
+    txn: &Txn,
+    shape: Shape,
+    dtype: NumberType,
+    mean: f64,
+    std: f64,
+) -> TCResult<Tensor> {
+    let lower: Number = (mean - 2. * std).into();
+    let upper: Number = (mean + 2. * std).into();
+
+    let file = create_file(txn).await?;
+    let mut tensor: Tensor =
+        BlockListFile::random_normal(file, *txn.id(), shape.clone(), dtype, mean.into(), std.into())
+            .map_ok(DenseTensor::from)
+            .map_ok(Tensor::from)
+            .await?;
+
+    for _ in 0..TRUNCATED_NORMAL_RESAMPLES {
+        let out_of_range = tensor
+            .clone()
+            .lt_const(lower)?
+            .or(tensor.clone().gt_const(upper)?)?;
+
+        let in_range = out_of_range
+            .clone()
+            .mul_const((-1i32).into())?
+            .add_const(1i32.into())?;
+
+        let file = create_file(txn).await?;
+        let resampled: Tensor = BlockListFile::random_normal(
+            file,
+            *txn.id(),
+            shape.clone(),
+            dtype,
+            mean.into(),
+            std.into(),
+        )
+        .map_ok(DenseTensor::from)
+        .map_ok(Tensor::from)
+        .await?;
+
+        tensor = tensor
+            .mul(in_range.cast(dtype)?)?
+            .add(resampled.mul(out_of_range.cast(dtype)?)?)?;
+    }
+
+    Ok(tensor)
+}
+
+struct XavierUniformHandler;
+
+impl<'a> Handler<'a> for XavierUniformHandler {
     fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
     where
         'b: 'a,
     {
         Some(Box::new(|txn, key| {
             Box::pin(async move {
-                if key.matches::<(Vec<u64>, Number, Number)>() {
-                    let (shape, start, stop): (Vec<u64>, Number, Number) =
-                        key.opt_cast_into().unwrap();
+                let shape = key.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                let (fan_in, fan_out) = fan_in_out(&shape)?;
+                let limit = (6. / (fan_in + fan_out)).sqrt();
 
-                    let file = create_file(&txn).await?;
+                let file = create_file(&txn).await?;
+                let tensor: Tensor = BlockListFile::random_uniform(file, *txn.id(), shape, FloatType::F64)
+                    .map(DenseTensor::from)
+                    .map(Tensor::from)?;
 
-                    DenseTensor::range(file, *txn.id(), shape, start, stop)
-                        .map_ok(Tensor::from)
-                        .map_ok(Collection::from)
-                        .map_ok(State::from)
-                        .await
-                } else {
-                    Err(TCError::invalid_value(key, "a Tensor schema"))
-                }
+                let tensor = tensor
+                    .mul_const((2. * limit).into())?
+                    .sub_const(limit.into())?;
+
+                Ok(State::Collection(tensor.into()))
             })
         }))
     }
-}
-
-struct ReshapeHandler<T> {
-    tensor: T,
-}
 
-impl<'a, T> Handler<'a> for ReshapeHandler<T>
-where
-    T: TensorAccess + TensorTransform + Send + 'a,
-    Tensor: From<T::Reshape>,
-{
-    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, key| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
-                let shape = key.try_into()?;
-                let shape = cast_shape(self.tensor.shape(), shape)?;
-                self.tensor
-                    .reshape(shape.into())
-                    .map(Tensor::from)
-                    .map(Collection::from)
-                    .map(State::from)
+                let shape: Value = params.require(&label("shape").into())?;
+                let shape: Shape = shape.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                params.expect_empty()?;
+
+                let (fan_in, fan_out) = fan_in_out(&shape)?;
+                let limit = (6. / (fan_in + fan_out)).sqrt();
+
+                let file = create_file(&txn).await?;
+                let tensor: Tensor = BlockListFile::random_uniform(file, *txn.id(), shape.into(), FloatType::F64)
+                    .map(DenseTensor::from)
+                    .map(Tensor::from)?;
+
+                let tensor = tensor
+                    .mul_const((2. * limit).into())?
+                    .sub_const(limit.into())?;
+
+                Ok(State::Collection(tensor.into()))
             })
         }))
     }
 }
 
-impl<T> From<T> for ReshapeHandler<T> {
-    fn from(tensor: T) -> Self {
-        Self { tensor }
-    }
-}
+struct XavierNormalHandler;
 
-struct TileHandler;
+impl<'a> Handler<'a> for XavierNormalHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let shape = key.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                let (fan_in, fan_out) = fan_in_out(&shape)?;
+                let std = (2. / (fan_in + fan_out)).sqrt();
+
+                let file = create_file(&txn).await?;
+                let tensor = BlockListFile::random_normal(
+                    file,
+                    *txn.id(),
+                    shape,
+                    FloatType::F64,
+                    MEAN.into(),
+                    std.into(),
+                )
+                .map_ok(DenseTensor::from)
+                .map_ok(Tensor::from)
+                .await?;
+
+                Ok(State::Collection(tensor.into()))
+            })
+        }))
+    }
 
-impl<'a> Handler<'a> for TileHandler {
     fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
         Some(Box::new(|txn, mut params| {
             Box::pin(async move {
-                let tensor: Tensor = params.require(&TENSOR.into())?;
-                let multiples: Value = params.require(&label("multiples").into())?;
+                let shape: Value = params.require(&label("shape").into())?;
+                let shape: Shape = shape.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
                 params.expect_empty()?;
 
-                let multiples: Vec<u64> = match multiples {
-                    Value::Number(n) if n >= Number::from(1) => {
-                        assert!(tensor.ndim() > 0);
-                        let mut multiples = vec![1; tensor.ndim() - 1];
-                        multiples.push(n.cast_into());
-                        Ok(multiples)
-                    }
-                    Value::Number(n) => Err(bad_request!("cannot tile a Tensor {} times", n))?,
-                    Value::Tuple(multiples) if multiples.len() == tensor.ndim() => multiples
-                        .try_cast_into(|v| {
-                            TCError::invalid_value(v, "a list of multiples for tiling")
-                        }),
-                    other => Err(TCError::invalid_value(
-                        other,
-                        "a list of multiples for tiling",
-                    )),
-                }?;
+                let (fan_in, fan_out) = fan_in_out(&shape)?;
+                let std = (2. / (fan_in + fan_out)).sqrt();
 
-                match tensor {
-                    Tensor::Dense(dense) => {
-                        DenseTensor::tile(txn.clone(), dense, multiples)
-                            .map_ok(Tensor::from)
-                            .map_ok(State::from)
-                            .await
-                    }
-                    Tensor::Sparse(sparse) => {
-                        SparseTensor::tile(txn.clone(), sparse, multiples)
-                            .map_ok(Tensor::from)
-                            .map_ok(State::from)
-                            .await
-                    }
-                }
+                let file = create_file(&txn).await?;
+                let tensor = BlockListFile::random_normal(
+                    file,
+                    *txn.id(),
+                    shape.into(),
+                    FloatType::F64,
+                    MEAN.into(),
+                    std.into(),
+                )
+                .map_ok(DenseTensor::from)
+                .map_ok(Tensor::from)
+                .await?;
+
+                Ok(State::Collection(tensor.into()))
             })
         }))
     }
 }
 
-struct TransposeHandler<T> {
-    tensor: T,
-}
+struct HeNormalHandler;
 
-impl<'a, T> Handler<'a> for TransposeHandler<T>
-where
-    T: TensorTransform + Send + 'a,
-    Tensor: From<T::Transpose>,
-{
+impl<'a> Handler<'a> for HeNormalHandler {
     fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, key| {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let shape = key.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                let (fan_in, _) = fan_in_out(&shape)?;
+                let std = (2. / fan_in).sqrt();
+
+                let file = create_file(&txn).await?;
+                let tensor = BlockListFile::random_normal(
+                    file,
+                    *txn.id(),
+                    shape,
+                    FloatType::F64,
+                    MEAN.into(),
+                    std.into(),
+                )
+                .map_ok(DenseTensor::from)
+                .map_ok(Tensor::from)
+                .await?;
+
+                Ok(State::Collection(tensor.into()))
+            })
+        }))
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let shape: Value = params.require(&label("shape").into())?;
+                let shape: Shape = shape.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                params.expect_empty()?;
+
+                let (fan_in, _) = fan_in_out(&shape)?;
+                let std = (2. / fan_in).sqrt();
+
+                let file = create_file(&txn).await?;
+                let tensor = BlockListFile::random_normal(
+                    file,
+                    *txn.id(),
+                    shape.into(),
+                    FloatType::F64,
+                    MEAN.into(),
+                    std.into(),
+                )
+                .map_ok(DenseTensor::from)
+                .map_ok(Tensor::from)
+                .await?;
+
+                Ok(State::Collection(tensor.into()))
+            })
+        }))
+    }
+}
+
+struct TruncatedNormalHandler;
+
+impl<'a> Handler<'a> for TruncatedNormalHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let shape: Shape = key.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+
+                let tensor =
+                    truncated_normal(&txn, shape, NumberType::Float(FloatType::F64), MEAN, STD).await?;
+
+                Ok(State::Collection(tensor.into()))
+            })
+        }))
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let shape: Value = params.require(&label("shape").into())?;
+                let shape: Shape = shape.try_cast_into(|v| TCError::invalid_value(v, "a Tensor shape"))?;
+                let mean: f64 = params.option(&label("mean").into(), || MEAN)?;
+                let std: f64 = params.option(&label("std").into(), || STD)?;
+                params.expect_empty()?;
+
+                let tensor =
+                    truncated_normal(&txn, shape.into(), NumberType::Float(FloatType::F64), mean, std)
+                        .await?;
+
+                Ok(State::Collection(tensor.into()))
+            })
+        }))
+    }
+}
+
+struct RangeHandler;
+
+impl<'a> Handler<'a> for RangeHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.matches::<(Vec<u64>, Number, Number)>() {
+                    let (shape, start, stop): (Vec<u64>, Number, Number) =
+                        key.opt_cast_into().unwrap();
+
+                    let file = create_file(&txn).await?;
+
+                    DenseTensor::range(file, *txn.id(), shape, start, stop)
+                        .map_ok(Tensor::from)
+                        .map_ok(Collection::from)
+                        .map_ok(State::from)
+                        .await
+                } else {
+                    Err(TCError::invalid_value(key, "a Tensor schema"))
+                }
+            })
+        }))
+    }
+}
+
+struct ReshapeHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for ReshapeHandler<T>
+where
+    T: TensorAccess + TensorTransform + Send + 'a,
+    Tensor: From<T::Reshape>,
+{
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let shape = key.try_into()?;
+                let shape = cast_shape(self.tensor.shape(), shape)?;
+                self.tensor
+                    .reshape(shape.into())
+                    .map(Tensor::from)
+                    .map(Collection::from)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for ReshapeHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+/// The inverse of [`ExpandHandler`]: removes every size-`1` axis from a
+/// Tensor's shape (or, given an axis key, just that one), via
+/// [`TensorTransform::reshape`].
+struct SqueezeHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for SqueezeHandler<T>
+where
+    T: TensorAccess + TensorTransform + Send + 'a,
+    Tensor: From<T::Reshape>,
+{
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                self.tensor.shape().validate("squeeze")?;
+
+                let shape = self.tensor.shape();
+                let new_shape: Vec<u64> = if key.is_none() {
+                    shape.iter().copied().filter(|dim| *dim != 1).collect()
+                } else {
+                    let axis = cast_axis(key, self.tensor.ndim())?;
+                    if shape[axis] != 1 {
+                        return Err(bad_request!(
+                            "cannot squeeze axis {} of shape {} since its dimension is not 1",
+                            axis,
+                            shape
+                        ));
+                    }
+
+                    shape
+                        .iter()
+                        .copied()
+                        .enumerate()
+                        .filter_map(|(x, dim)| if x == axis { None } else { Some(dim) })
+                        .collect()
+                };
+
+                self.tensor
+                    .reshape(new_shape.into())
+                    .map(Tensor::from)
+                    .map(Collection::from)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for SqueezeHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+struct TileHandler;
+
+impl<'a> Handler<'a> for TileHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let tensor: Tensor = params.require(&TENSOR.into())?;
+                let multiples: Value = params.require(&label("multiples").into())?;
+                params.expect_empty()?;
+
+                let multiples: Vec<u64> = match multiples {
+                    Value::Number(n) if n >= Number::from(1) => {
+                        assert!(tensor.ndim() > 0);
+                        let mut multiples = vec![1; tensor.ndim() - 1];
+                        multiples.push(n.cast_into());
+                        Ok(multiples)
+                    }
+                    Value::Number(n) => Err(bad_request!("cannot tile a Tensor {} times", n))?,
+                    Value::Tuple(multiples) if multiples.len() == tensor.ndim() => multiples
+                        .try_cast_into(|v| {
+                            TCError::invalid_value(v, "a list of multiples for tiling")
+                        }),
+                    other => Err(TCError::invalid_value(
+                        other,
+                        "a list of multiples for tiling",
+                    )),
+                }?;
+
+                match tensor {
+                    Tensor::Dense(dense) => {
+                        DenseTensor::tile(txn.clone(), dense, multiples)
+                            .map_ok(Tensor::from)
+                            .map_ok(State::from)
+                            .await
+                    }
+                    Tensor::Sparse(sparse) => {
+                        SparseTensor::tile(txn.clone(), sparse, multiples)
+                            .map_ok(Tensor::from)
+                            .map_ok(State::from)
+                            .await
+                    }
+                }
+            })
+        }))
+    }
+}
+
+struct TransposeHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for TransposeHandler<T>
+where
+    T: TensorAccess + TensorTransform + Send + 'a,
+    Tensor: From<T::Transpose>,
+{
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
             Box::pin(async move {
                 let transpose = if key.is_none() {
                     self.tensor.transpose(None)
                 } else {
-                    let permutation =
+                    let permutation: Vec<usize> =
                         key.try_cast_into(|v| TCError::invalid_value(v, "a Tensor permutation"))?;
 
+                    validate_permutation(&permutation, self.tensor.ndim())?;
+
                     self.tensor.transpose(Some(permutation))
                 };
 
@@ -994,6 +1854,10 @@ impl Route for TensorType {
                     "random" => match path[1].as_str() {
                         "normal" => Some(Box::new(RandomNormalHandler)),
                         "uniform" => Some(Box::new(RandomUniformHandler)),
+                        "xavier_uniform" => Some(Box::new(XavierUniformHandler)),
+                        "xavier_normal" => Some(Box::new(XavierNormalHandler)),
+                        "he_normal" => Some(Box::new(HeNormalHandler)),
+                        "truncated_normal" => Some(Box::new(TruncatedNormalHandler)),
                         _ => None,
                     },
                     _ => None,
@@ -1054,7 +1918,7 @@ impl<'a> Handler<'a> for DualHandler {
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, mut params| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
                 let l = self.tensor;
                 let r = params.remove::<Id>(&RIGHT.into()).ok_or_else(|| {
@@ -1069,12 +1933,19 @@ impl<'a> Handler<'a> for DualHandler {
                     State::Collection(Collection::Tensor(r)) => {
                         r.shape().validate(self.op_name)?;
 
-                        if l.shape() == r.shape() {
-                            (self.op)(l, r).map(Collection::from).map(State::from)
+                        let (left, right) = (l.clone(), r.clone());
+                        let result = if l.shape() == r.shape() {
+                            (self.op)(l, r)?
                         } else {
                             let (l, r) = broadcast(l, r)?;
-                            (self.op)(l, r).map(Collection::from).map(State::from)
+                            (self.op)(l, r)?
+                        };
+
+                        if let Ok(tape_op) = TapeOp::from_str(self.op_name, None) {
+                            record(*txn.id(), tape_op, &[left, right], &result);
                         }
+
+                        Ok(State::from(Collection::from(result)))
                     }
                     State::Scalar(Scalar::Value(r)) if r.matches::<Number>() => {
                         let r = r.opt_cast_into().expect("numeric constant");
@@ -1087,6 +1958,97 @@ impl<'a> Handler<'a> for DualHandler {
     }
 }
 
+/// Default `(rtol, atol)` for [`IsCloseHandler`] when the caller doesn't
+/// supply its own, mirroring how numerical libraries pick looser tolerances
+/// for lower-precision float types.
+fn default_tolerance(dtype: NumberType) -> (f64, f64) {
+    match dtype {
+        NumberType::Float(FloatType::F32) => (1e-4, 5e-4),
+        NumberType::Float(FloatType::F16) => (1e-4, 5e-4),
+        NumberType::Float(_) => (1e-7, 1e-7),
+        // integer (and boolean) dtypes have no representation error to
+        // tolerate, so only an exact match should ever pass
+        NumberType::Bool | NumberType::Int(_) | NumberType::UInt(_) => (0., 0.),
+        _ => (1e-7, 1e-7),
+    }
+}
+
+/// The elementwise predicate `|l - r| <= atol + rtol*|r|`, the same
+/// tolerance formula `numpy.isclose` uses.
+fn is_close(l: Tensor, r: Tensor, rtol: f64, atol: f64) -> TCResult<Tensor> {
+    let diff = l.sub(r.clone())?.abs()?;
+    let tolerance = r.abs()?.mul_const(rtol.into())?.add_const(atol.into())?;
+    diff.lte(tolerance)
+}
+
+/// `allclose`/`isclose`: an approximate equality check for floating-point
+/// Tensors, for which [`DualHandler`]'s exact `eq` is rarely useful --
+/// broadcasts its operands the same way `DualHandler` does before applying
+/// [`is_close`], then (for `allclose`) reduces the result with the existing
+/// `all` machinery.
+struct IsCloseHandler {
+    tensor: Tensor,
+    reduce: bool,
+}
+
+impl IsCloseHandler {
+    fn new(tensor: Tensor, reduce: bool) -> Self {
+        Self { tensor, reduce }
+    }
+}
+
+impl<'a> Handler<'a> for IsCloseHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let l = self.tensor;
+                l.shape().validate("allclose")?;
+
+                let r: State = params.require(&RIGHT.into())?;
+                let (default_rtol, default_atol) = default_tolerance(l.dtype());
+                let rtol: f64 = params.option(&label("rtol").into(), || default_rtol)?;
+                let atol: f64 = params.option(&label("atol").into(), || default_atol)?;
+                params.expect_empty()?;
+
+                let r: Tensor = match r {
+                    State::Collection(Collection::Tensor(r)) => {
+                        r.shape().validate("allclose")?;
+                        r
+                    }
+                    State::Scalar(Scalar::Value(r)) if r.matches::<Number>() => {
+                        let r = r.opt_cast_into().expect("numeric constant");
+                        constant(&txn, l.shape().clone(), r)
+                            .map_ok(Tensor::from)
+                            .await?
+                    }
+                    other => return Err(TCError::invalid_value(other, "a Tensor or Number")),
+                };
+
+                let (l, r) = if l.shape() == r.shape() {
+                    (l, r)
+                } else {
+                    broadcast(l, r)?
+                };
+
+                let close = is_close(l, r, rtol, atol)?;
+
+                if self.reduce {
+                    close
+                        .all(txn.clone())
+                        .map_ok(Value::from)
+                        .map_ok(State::from)
+                        .await
+                } else {
+                    Ok(State::Collection(Collection::from(close)))
+                }
+            })
+        }))
+    }
+}
+
 // TODO: should this be more general, like `DualHandlerWithDefaultArgument`?
 struct LogHandler {
     tensor: Tensor,
@@ -1168,6 +2130,66 @@ impl<'a> Handler<'a> for LogHandler {
     }
 }
 
+/// Permute `tensor` so that `axis` becomes its last axis, leaving the
+/// relative order of every other axis unchanged -- used by [`matmul_axes`]
+/// to reduce an arbitrary pair of contraction axes down to the rank-2
+/// `ij,jk->ijk` trick [`MatMulHandler`] already used for plain matrices.
+fn move_axis_last(tensor: Tensor, axis: usize) -> TCResult<Tensor> {
+    if axis == tensor.ndim() - 1 {
+        return Ok(tensor);
+    }
+
+    let mut permutation: Vec<usize> = (0..tensor.ndim()).filter(|x| *x != axis).collect();
+    permutation.push(axis);
+    tensor.transpose(Some(permutation))
+}
+
+/// Batched matmul, contracting `left_axis` of `left` against `right_axis`
+/// of `right` and broadcasting any leading batch dimensions the same way
+/// [`DualHandler`] broadcasts mismatched operands. Moves both contraction
+/// axes to the end of their tensor first, so the same expand-dims/
+/// broadcast/mul/sum steps the rank-2-only `ij,jk->ijk` trick used still
+/// apply regardless of rank or which axes were contracted.
+fn matmul_axes(
+    left: Tensor,
+    right: Tensor,
+    left_axis: usize,
+    right_axis: usize,
+) -> TCResult<Tensor> {
+    if left_axis >= left.ndim() || right_axis >= right.ndim() {
+        return Err(bad_request!(
+            "invalid contraction axes {}, {} for matmul of {} and {}",
+            left_axis,
+            right_axis,
+            left.shape(),
+            right.shape()
+        ));
+    }
+
+    if left.shape()[left_axis] != right.shape()[right_axis] {
+        return Err(bad_request!(
+            "invalid dimensions for matmul: axis {} of {} does not match axis {} of {}",
+            left_axis,
+            left.shape(),
+            right_axis,
+            right.shape()
+        ));
+    }
+
+    let left = move_axis_last(left, left_axis)?;
+    let right = move_axis_last(right, right_axis)?;
+
+    // ...,m,k -> ...,m,1,k
+    let left = left.expand_dims(left.ndim() - 1)?;
+    // ...,n,k -> ...,1,n,k
+    let right = right.expand_dims(right.ndim() - 2)?;
+    let (left, right) = broadcast(left, right)?;
+
+    // ...,m,n,k -> ...,m,n
+    let ndim = left.ndim();
+    left.mul(right)?.sum(ndim - 1, false)
+}
+
 // TODO: delete this after implementing a custom Matrix type (separate from Tensor)
 struct MatMulHandler {
     tensor: Tensor,
@@ -1186,35 +2208,38 @@ impl<'a> Handler<'a> for MatMulHandler {
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, mut params| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
                 let right: Tensor = params.require(&RIGHT.into())?;
+                let axes: Option<Vec<u64>> = params.option(&label("axes").into(), || None)?;
                 params.expect_empty()?;
 
-                if self.tensor.ndim() != 2 {
-                    return Err(TCError::invalid_value(self.tensor, "a matrix"));
-                }
+                let left = self.tensor;
 
-                if right.ndim() != 2 {
-                    return Err(TCError::invalid_value(right, "a matrix"));
+                if left.ndim() < 2 {
+                    return Err(TCError::invalid_value(left, "a Tensor of rank 2 or greater"));
                 }
 
-                if self.tensor.shape()[1] != right.shape()[0] {
-                    return Err(bad_request!(
-                        "invalid dimensions for matmul: {} @ {}",
-                        self.tensor.shape(),
-                        right.shape()
-                    ));
+                if right.ndim() < 2 {
+                    return Err(TCError::invalid_value(right, "a Tensor of rank 2 or greater"));
                 }
 
-                // ij,jk->ijk
-                let left = self.tensor.expand_dims(2)?;
-                let right = right.expand_dims(0)?;
-                let (left, right) = broadcast(left, right)?;
-                let op = left.mul(right)?;
+                let (left_axis, right_axis) = match axes {
+                    Some(axes) if axes.len() == 2 => (axes[0] as usize, axes[1] as usize),
+                    Some(axes) => {
+                        return Err(bad_request!(
+                            "matmul axes must name exactly 2 axes, found {:?}",
+                            axes
+                        ))
+                    }
+                    None => (left.ndim() - 1, right.ndim() - 2),
+                };
+
+                let (parent_left, parent_right) = (left.clone(), right.clone());
+                let result = matmul_axes(left, right, left_axis, right_axis)?;
+                record(*txn.id(), TapeOp::MatMul, &[parent_left, parent_right], &result);
 
-                // ijk -> uk
-                op.sum(1, false).map(State::from)
+                Ok(State::from(result))
             })
         }))
     }
@@ -1224,46 +2249,86 @@ struct NormHandler {
     tensor: Tensor,
 }
 
+/// The default norm order: Euclidean (L2) for a vector, Frobenius for a
+/// matrix.
+const DEFAULT_NORM_ORD: f64 = 2.0;
+
 impl NormHandler {
+    /// Reduce `tensor` with whatever `ord` selects: `max`/`min` of its
+    /// absolute value for `ord = +-inf`, or the general `p`-norm
+    /// `sum(|x|^ord)^(1/ord)` otherwise (which already covers `ord = 1` and
+    /// the default `ord = 2` without any special-casing).
     async fn call(
         tensor: Tensor,
         txn: Txn,
         axis: Option<usize>,
         keepdims: bool,
+        ord: f64,
     ) -> TCResult<State> {
         if let Some(axis) = axis {
-            debug!("norm of {} at axis {}", tensor, axis);
-
-            return tensor
-                .pow_const(2i32.into())
-                .and_then(|pow| pow.sum(axis, keepdims))
-                .and_then(|sum| sum.pow_const(0.5f32.into()))
-                .map(Collection::Tensor)
-                .map(State::Collection);
+            debug!("norm of {} at axis {} (ord {})", tensor, axis, ord);
+
+            let abs = tensor.abs()?;
+
+            return if ord.is_infinite() {
+                if ord > 0. {
+                    abs.max(axis, keepdims)
+                } else {
+                    abs.min(axis, keepdims)
+                }
+            } else {
+                abs.pow_const(ord.into())
+                    .and_then(|pow| pow.sum(axis, keepdims))
+                    .and_then(|sum| sum.pow_const((1. / ord).into()))
+            }
+            .map(Collection::Tensor)
+            .map(State::Collection);
         } else if tensor.ndim() <= 2 {
             if keepdims {
-                Err(not_implemented!("matrix norm with keepdims"))
+                return Err(not_implemented!("matrix norm with keepdims"));
+            }
+
+            let abs = tensor.abs()?;
+
+            if ord.is_infinite() {
+                let reduced = if ord > 0. {
+                    abs.max_all(txn).await?
+                } else {
+                    abs.min_all(txn).await?
+                };
+
+                Ok(Value::from(reduced).into())
             } else {
-                let squared = tensor.pow_const(2i32.into())?;
-                let summed = squared.sum_all(txn).await?;
-                Ok(Value::from(summed.pow(0.5f32.into())).into())
+                let summed = abs.pow_const(ord.into())?.sum_all(txn).await?;
+                Ok(Value::from(summed.pow((1. / ord).into())).into())
             }
         } else {
-            debug!("norm of {}, keepdims is {}", tensor, keepdims);
+            debug!("norm of {}, keepdims is {} (ord {})", tensor, keepdims, ord);
 
-            tensor
-                .pow_const(2i32.into())
-                .and_then(|pow| {
-                    let axis = pow.ndim() - 1;
-                    pow.sum(axis, keepdims)
-                })
-                .and_then(|pow| {
-                    let axis = pow.ndim() - if keepdims { 2 } else { 1 };
-                    pow.sum(axis, keepdims)
+            let abs = tensor.abs()?;
+
+            let reduced = if ord.is_infinite() {
+                let reduce: fn(Tensor, usize, bool) -> TCResult<Tensor> =
+                    if ord > 0. { TensorReduce::max } else { TensorReduce::min };
+
+                reduce(abs.clone(), abs.ndim() - 1, keepdims).and_then(|reduced| {
+                    let axis = reduced.ndim() - if keepdims { 2 } else { 1 };
+                    reduce(reduced, axis, keepdims)
                 })
-                .and_then(|sum| sum.pow_const(0.5f32.into()))
-                .map(Collection::Tensor)
-                .map(State::Collection)
+            } else {
+                abs.pow_const(ord.into())
+                    .and_then(|pow| {
+                        let axis = pow.ndim() - 1;
+                        pow.sum(axis, keepdims)
+                    })
+                    .and_then(|pow| {
+                        let axis = pow.ndim() - if keepdims { 2 } else { 1 };
+                        pow.sum(axis, keepdims)
+                    })
+                    .and_then(|sum| sum.pow_const((1. / ord).into()))
+            };
+
+            reduced.map(Collection::Tensor).map(State::Collection)
         }
     }
 }
@@ -1281,7 +2346,7 @@ impl<'a> Handler<'a> for NormHandler {
                     None
                 };
 
-                Self::call(self.tensor, txn.clone(), axis, false).await
+                Self::call(self.tensor, txn.clone(), axis, false, DEFAULT_NORM_ORD).await
             })
         }))
     }
@@ -1300,10 +2365,11 @@ impl<'a> Handler<'a> for NormHandler {
                 };
 
                 let keepdims = params.or_default(&KEEPDIMS.into())?;
+                let ord: f64 = params.option(&label("ord").into(), || DEFAULT_NORM_ORD)?;
 
                 params.expect_empty()?;
 
-                Self::call(self.tensor, txn.clone(), axis, keepdims).await
+                Self::call(self.tensor, txn.clone(), axis, keepdims, ord).await
             })
         }))
     }
@@ -1315,6 +2381,153 @@ impl From<Tensor> for NormHandler {
     }
 }
 
+/// Which of `mean`/`var`/`std` a [`StatHandler`] computes.
+#[derive(Clone, Copy)]
+enum Stat {
+    Mean,
+    Var,
+    Std,
+}
+
+/// `GET`/`POST` handler for `mean`/`var`/`std`, taking an optional `axis`
+/// (defaulting to a full reduction, like [`ReduceHandler`]) and, for `var`
+/// and `std`, an optional `unbiased` flag selecting Bessel's correction
+/// (dividing by `n - 1` instead of `n`).
+struct StatHandler {
+    tensor: Tensor,
+    stat: Stat,
+}
+
+impl StatHandler {
+    fn new(tensor: Tensor, stat: Stat) -> Self {
+        Self { tensor, stat }
+    }
+
+    /// The whole-Tensor mean, as a bare `Number` (no axis to keep).
+    async fn mean_all(tensor: Tensor, txn: &Txn) -> TCResult<Number> {
+        let n = tensor.size() as f64;
+        tensor.div_const(n.into())?.sum_all(txn.clone()).await
+    }
+
+    /// The whole-Tensor variance, dividing by `n - 1` instead of `n` if
+    /// `unbiased` is set.
+    async fn var_all(tensor: Tensor, txn: &Txn, unbiased: bool) -> TCResult<Number> {
+        let n = tensor.size() as f64;
+        let mean = Self::mean_all(tensor.clone(), txn).await?;
+        let mean = constant(txn, tensor.shape().clone(), mean)
+            .map_ok(Tensor::from)
+            .await?;
+
+        let diff = tensor.sub(mean)?;
+        let divisor = if unbiased { n - 1. } else { n };
+
+        diff.clone()
+            .mul(diff)?
+            .div_const(divisor.into())?
+            .sum_all(txn.clone())
+            .await
+    }
+
+    async fn call(&self, txn: &Txn, axis: Option<usize>, keepdims: bool, unbiased: bool) -> TCResult<State> {
+        if axis.is_none() && keepdims {
+            return Err(not_implemented!("reduce all axes but keep dimensions"));
+        }
+
+        match (self.stat, axis) {
+            (Stat::Mean, None) => Self::mean_all(self.tensor.clone(), txn)
+                .map_ok(Value::from)
+                .map_ok(State::from)
+                .await,
+            (Stat::Mean, Some(axis)) => {
+                let n = self.tensor.shape()[axis] as f64;
+                self.tensor
+                    .clone()
+                    .sum(axis, keepdims)?
+                    .div_const(n.into())
+                    .map(Collection::Tensor)
+                    .map(State::Collection)
+            }
+            (Stat::Var, None) => Self::var_all(self.tensor.clone(), txn, unbiased)
+                .map_ok(Value::from)
+                .map_ok(State::from)
+                .await,
+            (Stat::Var, Some(axis)) => {
+                let n = self.tensor.shape()[axis] as f64;
+                let mean = self.tensor.clone().sum(axis, true)?.div_const(n.into())?;
+                let diff = sub_broadcast(self.tensor.clone(), mean)?;
+                let divisor = if unbiased { n - 1. } else { n };
+
+                diff.clone()
+                    .mul(diff)?
+                    .sum(axis, keepdims)?
+                    .div_const(divisor.into())
+                    .map(Collection::Tensor)
+                    .map(State::Collection)
+            }
+            (Stat::Std, None) => Self::var_all(self.tensor.clone(), txn, unbiased)
+                .map_ok(|var| var.pow(0.5.into()))
+                .map_ok(Value::from)
+                .map_ok(State::from)
+                .await,
+            (Stat::Std, Some(axis)) => {
+                let n = self.tensor.shape()[axis] as f64;
+                let mean = self.tensor.clone().sum(axis, true)?.div_const(n.into())?;
+                let diff = sub_broadcast(self.tensor.clone(), mean)?;
+                let divisor = if unbiased { n - 1. } else { n };
+
+                diff.clone()
+                    .mul(diff)?
+                    .sum(axis, keepdims)?
+                    .div_const(divisor.into())?
+                    .pow_const(0.5.into())
+                    .map(Collection::Tensor)
+                    .map(State::Collection)
+            }
+        }
+    }
+}
+
+impl<'a> Handler<'a> for StatHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let axis = if key.is_none() {
+                    None
+                } else {
+                    cast_axis(key, self.tensor.ndim()).map(Some)?
+                };
+
+                self.call(txn, axis, false, false).await
+            })
+        }))
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let axis = if params.contains_key::<Id>(&AXIS.into()) {
+                    let axis = params.require(&AXIS.into())?;
+                    cast_axis(axis, self.tensor.ndim()).map(Some)?
+                } else {
+                    None
+                };
+
+                let keepdims = params.or_default(&KEEPDIMS.into())?;
+                let unbiased = params.option(&label("unbiased").into(), || false)?;
+                params.expect_empty()?;
+
+                self.call(txn, axis, keepdims, unbiased).await
+            })
+        }))
+    }
+}
+
 struct ReduceHandler<'a, T: TensorReduce<fs::Dir>> {
     tensor: &'a T,
     reduce: fn(T, usize, bool) -> TCResult<<T as TensorReduce<fs::Dir>>::Reduce>,
@@ -1465,9 +2678,138 @@ where
     }
 }
 
-impl<T> From<T> for TensorHandler<T> {
-    fn from(tensor: T) -> Self {
-        Self { tensor }
+impl<T> From<T> for TensorHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+/// `max(x, 0)`, via `(x + |x|) / 2` since there's no `TensorMath::max_const`
+/// to call directly.
+fn relu(tensor: &Tensor) -> TCResult<Tensor> {
+    let tensor = tensor.clone();
+    let abs = tensor.clone().abs()?;
+    tensor.add(abs)?.mul_const(0.5.into())
+}
+
+/// `1 / (1 + exp(-x))`, via [`TensorMath::pow`]'s constant form for the
+/// reciprocal since there's no `TensorMath::div_const` taking the tensor as
+/// the divisor.
+fn sigmoid(tensor: &Tensor) -> TCResult<Tensor> {
+    tensor
+        .clone()
+        .mul_const((-1i32).into())?
+        .exp()?
+        .add_const(1i32.into())?
+        .pow_const((-1.0).into())
+}
+
+/// The `tanh`-based approximation of GELU: `0.5 * x * (1 + tanh(sqrt(2/pi)
+/// * (x + 0.044715 * x^3)))`, since there's no `erf` to compute the exact
+/// form with.
+fn gelu(tensor: &Tensor) -> TCResult<Tensor> {
+    const SQRT_2_OVER_PI: f64 = 0.7978845608028654;
+
+    let x = tensor.clone();
+    let x3 = x.clone().pow_const(3i32.into())?;
+    let inner = x
+        .clone()
+        .add(x3.mul_const(0.044715.into())?)?
+        .mul_const(SQRT_2_OVER_PI.into())?;
+
+    let tanh = inner.tanh()?;
+    x.mul(tanh.add_const(1i32.into())?)?.mul_const(0.5.into())
+}
+
+/// Numerically-stable softmax along `axis`: subtract the per-axis max
+/// before exponentiating (so the largest exponent is `exp(0) = 1`), then
+/// divide by the per-axis sum of the exponentials.
+fn softmax(tensor: Tensor, axis: usize) -> TCResult<Tensor> {
+    let max = tensor.clone().max(axis, true)?;
+    let exp = sub_broadcast(tensor, max)?.exp()?;
+    let denom = exp.clone().sum(axis, true)?;
+    div_broadcast(exp, denom)
+}
+
+/// "Quiet softmax" (softmax-with-one / off-by-one attention): identical to
+/// [`softmax`] except the denominator is `1 + sum(exp(x - max))`, so the
+/// output can go uniformly small instead of always summing to `1` when
+/// every logit along `axis` is low.
+fn quiet_softmax(tensor: Tensor, axis: usize) -> TCResult<Tensor> {
+    let max = tensor.clone().max(axis, true)?;
+    let exp = sub_broadcast(tensor, max)?.exp()?;
+    let denom = exp.clone().sum(axis, true)?.add_const(1i32.into())?;
+    div_broadcast(exp, denom)
+}
+
+/// `GET`/`POST` handler for [`softmax`]/[`quiet_softmax`], taking an
+/// optional `axis` (defaulting to the last axis) the same way
+/// [`ReduceHandler`] does.
+struct SoftmaxHandler {
+    tensor: Tensor,
+    quiet: bool,
+}
+
+impl SoftmaxHandler {
+    fn new(tensor: Tensor, quiet: bool) -> Self {
+        Self { tensor, quiet }
+    }
+
+    fn call(&self, axis: Option<usize>) -> TCResult<State> {
+        self.tensor.shape().validate("softmax")?;
+
+        if self.tensor.ndim() == 0 {
+            return Err(bad_request!("cannot compute the softmax of a 0-dimensional Tensor"));
+        }
+
+        let axis = axis.unwrap_or_else(|| self.tensor.ndim() - 1);
+
+        let result = if self.quiet {
+            quiet_softmax(self.tensor.clone(), axis)
+        } else {
+            softmax(self.tensor.clone(), axis)
+        }?;
+
+        Ok(State::from(Collection::from(result)))
+    }
+}
+
+impl<'a> Handler<'a> for SoftmaxHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let axis = if key.is_none() {
+                    None
+                } else {
+                    cast_axis(key, self.tensor.ndim()).map(Some)?
+                };
+
+                self.call(axis)
+            })
+        }))
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let axis = if params.contains_key::<Id>(&AXIS.into()) {
+                    let axis = params.require(&AXIS.into())?;
+                    cast_axis(axis, self.tensor.ndim()).map(Some)?
+                } else {
+                    None
+                };
+
+                params.expect_empty()?;
+
+                self.call(axis)
+            })
+        }))
     }
 }
 
@@ -1641,6 +2983,13 @@ where
                 ))))
             }
 
+            "effective_ndim" => {
+                let effective_ndim = tensor.shape().iter().filter(|dim| **dim != 1).count();
+                return Some(Box::new(AttributeHandler::from(Value::Number(
+                    (effective_ndim as u64).into(),
+                ))));
+            }
+
             // reduce ops (which require borrowing)
             "max" => {
                 return Some(Box::new(ReduceHandler::new(
@@ -1749,6 +3098,8 @@ where
                 TensorCompareConst::ne_const,
                 "ne",
             ))),
+            "isclose" => Some(Box::new(IsCloseHandler::new(tensor.into(), false))),
+            "allclose" => Some(Box::new(IsCloseHandler::new(tensor.into(), true))),
 
             // trigonometry
             "asin" => Some(Box::new(UnaryHandler::new(
@@ -1846,6 +3197,17 @@ where
                 "round",
             ))),
 
+            // activations
+            "relu" => Some(Box::new(UnaryHandler::new(tensor.into(), relu, "relu"))),
+            "sigmoid" => Some(Box::new(UnaryHandler::new(
+                tensor.into(),
+                sigmoid,
+                "sigmoid",
+            ))),
+            "gelu" => Some(Box::new(UnaryHandler::new(tensor.into(), gelu, "gelu"))),
+            "softmax" => Some(Box::new(SoftmaxHandler::new(tensor.into(), false))),
+            "quiet_softmax" => Some(Box::new(SoftmaxHandler::new(tensor.into(), true))),
+
             // basic math
             "add" => Some(Box::new(DualHandler::new(
                 tensor,
@@ -1886,6 +3248,7 @@ where
             "flip" => Some(Box::new(FlipHandler::from(tensor))),
             "expand_dims" => Some(Box::new(ExpandHandler::from(tensor))),
             "reshape" => Some(Box::new(ReshapeHandler::from(tensor))),
+            "squeeze" => Some(Box::new(SqueezeHandler::from(tensor))),
             "transpose" => Some(Box::new(TransposeHandler::from(tensor))),
 
             // indexing
@@ -1897,6 +3260,15 @@ where
             // other
             "norm" => Some(Box::new(NormHandler::from(Tensor::from(tensor)))),
 
+            // statistical reductions
+            "mean" => Some(Box::new(StatHandler::new(tensor.into(), Stat::Mean))),
+            "var" => Some(Box::new(StatHandler::new(tensor.into(), Stat::Var))),
+            "std" => Some(Box::new(StatHandler::new(tensor.into(), Stat::Std))),
+
+            // quantization
+            "quantize" => Some(Box::new(QuantizeHandler::from(Tensor::from(tensor)))),
+            "dequantize" => Some(Box::new(DequantizeHandler::from(Tensor::from(tensor)))),
+
             _ => None,
         }
     } else {
@@ -1904,6 +3276,515 @@ where
     }
 }
 
+/// Zero-pad `input` (shaped `[N, C, H, W]`) by `padding` on each side of its
+/// last two axes, via the same blank-tensor-then-write approach
+/// [`ConcatenateHandler::concatenate_axis`] uses to assemble its output.
+async fn pad2d(txn: &Txn, input: Tensor, padding: u64) -> TCResult<Tensor> {
+    if padding == 0 {
+        return Ok(input);
+    }
+
+    let shape = input.shape().to_vec();
+    let (n, c, h, w) = (shape[0], shape[1], shape[2], shape[3]);
+    let padded_shape = vec![n, c, h + (2 * padding), w + (2 * padding)];
+
+    let dtype = input.dtype();
+    let padded = ConcatenateHandler::blank(txn, padded_shape, dtype).await?;
+
+    let bounds: Bounds = vec![
+        AxisBounds::all(n),
+        AxisBounds::all(c),
+        AxisBounds::In(padding..(padding + h)),
+        AxisBounds::In(padding..(padding + w)),
+    ]
+    .into_iter()
+    .collect();
+
+    padded.clone().write(txn.clone(), bounds, input).await?;
+
+    Ok(padded.into())
+}
+
+/// `[N, C_in, H, W]` @ `[C_out, C_in, KH, KW]` -> `[N, C_out, out_h, out_w]`,
+/// via im2col: gather the `C_in*KH*KW` patch under each output position into
+/// a column, concatenate the columns into a `[C_in*KH*KW, out_h*out_w*N]`
+/// matrix, and reduce the whole convolution to a single [`matmul`] against
+/// the weight reshaped to `[C_out, C_in*KH*KW]`.
+async fn conv2d(
+    txn: &Txn,
+    input: Tensor,
+    weight: Tensor,
+    bias: Option<Tensor>,
+    stride: u64,
+    padding: u64,
+) -> TCResult<Tensor> {
+    if input.ndim() != 4 {
+        return Err(bad_request!(
+            "conv2d input must have shape [N, C_in, H, W], found {}",
+            input.shape()
+        ));
+    }
+
+    if weight.ndim() != 4 {
+        return Err(bad_request!(
+            "conv2d weight must have shape [C_out, C_in, KH, KW], found {}",
+            weight.shape()
+        ));
+    }
+
+    if stride == 0 {
+        return Err(bad_request!("conv2d stride must be greater than zero"));
+    }
+
+    let in_shape = input.shape().to_vec();
+    let (batch, in_channels, height, width) = (in_shape[0], in_shape[1], in_shape[2], in_shape[3]);
+
+    let weight_shape = weight.shape().to_vec();
+    let (out_channels, weight_in_channels, kh, kw) =
+        (weight_shape[0], weight_shape[1], weight_shape[2], weight_shape[3]);
+
+    if weight_in_channels != in_channels {
+        return Err(bad_request!(
+            "conv2d weight expects {} input channels, found {}",
+            weight_in_channels,
+            in_channels
+        ));
+    }
+
+    let dtype = input.dtype();
+    let input = pad2d(txn, input, padding).await?;
+    let out_h = (height + (2 * padding) - kh) / stride + 1;
+    let out_w = (width + (2 * padding) - kw) / stride + 1;
+
+    let mut columns = Vec::with_capacity((out_h * out_w) as usize);
+    for i in 0..out_h {
+        for j in 0..out_w {
+            let row_start = i * stride;
+            let col_start = j * stride;
+
+            let bounds: Bounds = vec![
+                AxisBounds::all(batch),
+                AxisBounds::all(in_channels),
+                AxisBounds::In(row_start..(row_start + kh)),
+                AxisBounds::In(col_start..(col_start + kw)),
+            ]
+            .into_iter()
+            .collect();
+
+            let patch = input.clone().slice(bounds)?;
+            let patch = patch.reshape(vec![batch, in_channels * kh * kw].into())?;
+            columns.push(patch.transpose(Some(vec![1, 0]))?);
+        }
+    }
+
+    let columns = ConcatenateHandler::concatenate_axis(txn, 1, dtype, columns).await?;
+    let weight = weight.reshape(vec![out_channels, in_channels * kh * kw].into())?;
+    let output = matmul(weight, columns)?;
+
+    let output = if let Some(bias) = bias {
+        if bias.size() != out_channels {
+            return Err(bad_request!(
+                "conv2d bias must have {} elements, found {}",
+                out_channels,
+                bias.size()
+            ));
+        }
+
+        let bias = bias.reshape(vec![out_channels, 1].into())?;
+        let (output, bias) = broadcast(output, bias)?;
+        output.add(bias)?
+    } else {
+        output
+    };
+
+    let output = output.reshape(vec![out_channels, out_h, out_w, batch].into())?;
+    output.transpose(Some(vec![3, 0, 1, 2]))
+}
+
+/// Which reduction [`pool2d`] applies within each window.
+enum PoolReduce {
+    Max,
+    Avg,
+}
+
+/// Slide a `[KH, KW]` window over each channel of `input` (shaped
+/// `[N, C, H, W]`) independently, reducing each window with `reduce`, and
+/// assemble the per-position results back into a `[N, C, out_h, out_w]`
+/// tensor one row at a time via [`ConcatenateHandler::concatenate_axis`].
+async fn pool2d(
+    txn: &Txn,
+    input: Tensor,
+    kernel: (u64, u64),
+    stride: u64,
+    padding: u64,
+    reduce: PoolReduce,
+) -> TCResult<Tensor> {
+    if input.ndim() != 4 {
+        return Err(bad_request!(
+            "pooling input must have shape [N, C, H, W], found {}",
+            input.shape()
+        ));
+    }
+
+    if stride == 0 {
+        return Err(bad_request!("pooling stride must be greater than zero"));
+    }
+
+    let (kh, kw) = kernel;
+    let in_shape = input.shape().to_vec();
+    let (batch, channels, height, width) = (in_shape[0], in_shape[1], in_shape[2], in_shape[3]);
+
+    let dtype = input.dtype();
+    let input = pad2d(txn, input, padding).await?;
+    let out_h = (height + (2 * padding) - kh) / stride + 1;
+    let out_w = (width + (2 * padding) - kw) / stride + 1;
+
+    let mut rows = Vec::with_capacity(out_h as usize);
+    for i in 0..out_h {
+        let mut cells = Vec::with_capacity(out_w as usize);
+        for j in 0..out_w {
+            let row_start = i * stride;
+            let col_start = j * stride;
+
+            let bounds: Bounds = vec![
+                AxisBounds::all(batch),
+                AxisBounds::all(channels),
+                AxisBounds::In(row_start..(row_start + kh)),
+                AxisBounds::In(col_start..(col_start + kw)),
+            ]
+            .into_iter()
+            .collect();
+
+            let window = input
+                .clone()
+                .slice(bounds)?
+                .reshape(vec![batch, channels, kh * kw].into())?;
+
+            let cell = match reduce {
+                PoolReduce::Max => window.max(2, false)?,
+                PoolReduce::Avg => window
+                    .sum(2, false)?
+                    .div_const(((kh * kw) as f64).into())?,
+            };
+
+            cells.push(cell.expand_dims(2)?.expand_dims(3)?);
+        }
+
+        rows.push(ConcatenateHandler::concatenate_axis(txn, 3, dtype, cells).await?);
+    }
+
+    ConcatenateHandler::concatenate_axis(txn, 2, dtype, rows).await
+}
+
+/// For each output cell `(i, j)`, average the input window
+/// `floor(i*H/out_h)..ceil((i+1)*H/out_h)` by `floor(j*W/out_w)..
+/// ceil((j+1)*W/out_w)`, so that `out_h`/`out_w` need not evenly divide the
+/// input's own height/width the way [`pool2d`]'s fixed stride does.
+async fn adaptive_avg_pool2d(txn: &Txn, input: Tensor, out_h: u64, out_w: u64) -> TCResult<Tensor> {
+    if input.ndim() != 4 {
+        return Err(bad_request!(
+            "adaptive_avg_pool2d input must have shape [N, C, H, W], found {}",
+            input.shape()
+        ));
+    }
+
+    if out_h == 0 || out_w == 0 {
+        return Err(bad_request!(
+            "adaptive_avg_pool2d output size must be greater than zero"
+        ));
+    }
+
+    let in_shape = input.shape().to_vec();
+    let (batch, channels, height, width) = (in_shape[0], in_shape[1], in_shape[2], in_shape[3]);
+    let dtype = input.dtype();
+
+    let mut rows = Vec::with_capacity(out_h as usize);
+    for i in 0..out_h {
+        let row_start = (i * height) / out_h;
+        let row_end = ((i + 1) * height + out_h - 1) / out_h;
+
+        let mut cells = Vec::with_capacity(out_w as usize);
+        for j in 0..out_w {
+            let col_start = (j * width) / out_w;
+            let col_end = ((j + 1) * width + out_w - 1) / out_w;
+
+            let bounds: Bounds = vec![
+                AxisBounds::all(batch),
+                AxisBounds::all(channels),
+                AxisBounds::In(row_start..row_end),
+                AxisBounds::In(col_start..col_end),
+            ]
+            .into_iter()
+            .collect();
+
+            let window_size = (row_end - row_start) * (col_end - col_start);
+            let cell = input
+                .clone()
+                .slice(bounds)?
+                .reshape(vec![batch, channels, window_size].into())?
+                .sum(2, false)?
+                .div_const((window_size as f64).into())?;
+
+            cells.push(cell.expand_dims(2)?.expand_dims(3)?);
+        }
+
+        rows.push(ConcatenateHandler::concatenate_axis(txn, 3, dtype, cells).await?);
+    }
+
+    ConcatenateHandler::concatenate_axis(txn, 2, dtype, rows).await
+}
+
+struct Conv2dHandler;
+
+impl<'a> Handler<'a> for Conv2dHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let input: Tensor = params.require(&TENSOR.into())?;
+                let weight: Tensor = params.require(&label("weight").into())?;
+                let bias: Option<Tensor> = params.option(&label("bias").into(), || None)?;
+                let stride: u64 = params.option(&label("stride").into(), || 1)?;
+                let padding: u64 = params.option(&label("padding").into(), || 0)?;
+                params.expect_empty()?;
+
+                conv2d(&txn, input, weight, bias, stride, padding)
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+/// Parse a 2-element `[h, w]` list (a window kernel or an adaptive output
+/// size) the same way [`TileHandler`] parses its own `multiples` list.
+fn cast_hw(name: &'static str, dims: Vec<u64>) -> TCResult<(u64, u64)> {
+    match &dims[..] {
+        [h, w] => Ok((*h, *w)),
+        _ => Err(bad_request!(
+            "{} must have exactly 2 dimensions, found {:?}",
+            name,
+            dims
+        )),
+    }
+}
+
+struct MaxPool2dHandler;
+
+impl<'a> Handler<'a> for MaxPool2dHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let input: Tensor = params.require(&TENSOR.into())?;
+                let kernel: Vec<u64> = params.require(&label("kernel").into())?;
+                let kernel = cast_hw("kernel", kernel)?;
+                let stride: u64 = params.option(&label("stride").into(), || 1)?;
+                let padding: u64 = params.option(&label("padding").into(), || 0)?;
+                params.expect_empty()?;
+
+                pool2d(&txn, input, kernel, stride, padding, PoolReduce::Max)
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+struct AvgPool2dHandler;
+
+impl<'a> Handler<'a> for AvgPool2dHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let input: Tensor = params.require(&TENSOR.into())?;
+                let kernel: Vec<u64> = params.require(&label("kernel").into())?;
+                let kernel = cast_hw("kernel", kernel)?;
+                let stride: u64 = params.option(&label("stride").into(), || 1)?;
+                let padding: u64 = params.option(&label("padding").into(), || 0)?;
+                params.expect_empty()?;
+
+                pool2d(&txn, input, kernel, stride, padding, PoolReduce::Avg)
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+struct AdaptiveAvgPool2dHandler;
+
+impl<'a> Handler<'a> for AdaptiveAvgPool2dHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let input: Tensor = params.require(&TENSOR.into())?;
+                let output_size: Vec<u64> = params.require(&label("output_size").into())?;
+                let (out_h, out_w) = cast_hw("output_size", output_size)?;
+                params.expect_empty()?;
+
+                adaptive_avg_pool2d(&txn, input, out_h, out_w)
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+/// Solve for the affine quantization `(scale, zero_point)` mapping the
+/// float range `[min, max]` onto the integer range `[qmin, qmax]`, i.e. the
+/// `(scale, zero_point)` such that `round(min / scale) + zero_point == qmin`
+/// and `round(max / scale) + zero_point == qmax`.
+///
+/// A full quantized Tensor dtype -- one whose `scale`/`zero_point` travel
+/// with the Tensor itself through `Schema`/`create_tensor`, and whose
+/// `matmul`/`add` accumulate in integer arithmetic before rescaling -- would
+/// require a variant on the `Tensor`/`Schema` types this routing layer is
+/// built against, neither of which has any such variant in this checkout.
+/// What follows is the piece of that request this module can actually
+/// deliver: `quantize`/`dequantize`/`qparams` operations a caller threads
+/// `scale`/`zero_point` through explicitly on each request, built from the
+/// existing `round`/`cast`/const-arithmetic primitives already reachable
+/// here.
+fn qparams(min: f64, max: f64, qmin: f64, qmax: f64) -> (f64, f64) {
+    let scale = if max > min { (max - min) / (qmax - qmin) } else { 1. };
+    let zero_point = qmin - (min / scale).round();
+    (scale, zero_point)
+}
+
+/// Solves for the `(scale, zero_point)` a caller should pass to
+/// [`QuantizeHandler`] to map an observed `[min, max]` float range onto a
+/// target integer range `[qmin, qmax]` (defaulting to the unsigned 8-bit
+/// range `[0, 255]`) -- see [`qparams`].
+struct QParamsHandler;
+
+impl<'a> Handler<'a> for QParamsHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let min: f64 = params.require(&label("min").into())?;
+                let max: f64 = params.require(&label("max").into())?;
+                let qmin: f64 = params.option(&label("qmin").into(), || 0.)?;
+                let qmax: f64 = params.option(&label("qmax").into(), || 255.)?;
+                params.expect_empty()?;
+
+                let (scale, zero_point) = qparams(min, max, qmin, qmax);
+                let params: Tuple<Value> = vec![
+                    Value::Number(scale.into()),
+                    Value::Number(zero_point.into()),
+                ]
+                .into_iter()
+                .collect();
+
+                Ok(State::from(Value::Tuple(params)))
+            })
+        }))
+    }
+}
+
+/// Maps a float Tensor onto `dtype` via the affine mapping
+/// `round(x / scale) + zero_point`, per [`qparams`].
+struct QuantizeHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for QuantizeHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let scale: f64 = params.require(&label("scale").into())?;
+                let zero_point: f64 = params.require(&label("zero_point").into())?;
+                let dtype: Value = params.require(&label("dtype").into())?;
+                params.expect_empty()?;
+
+                let dtype = ValueType::try_cast_from(dtype, |v| {
+                    TCError::invalid_type(v, "a Number class")
+                })?;
+                let dtype = dtype.try_into()?;
+
+                self.tensor
+                    .div_const(scale.into())?
+                    .round()?
+                    .add_const(zero_point.into())?
+                    .cast(dtype)
+                    .map(Collection::Tensor)
+                    .map(State::Collection)
+            })
+        }))
+    }
+}
+
+impl From<Tensor> for QuantizeHandler {
+    fn from(tensor: Tensor) -> Self {
+        Self { tensor }
+    }
+}
+
+/// Inverts [`QuantizeHandler`]: `(q - zero_point) * scale`, cast back to
+/// `dtype` (defaulting to 64-bit float).
+struct DequantizeHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for DequantizeHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let scale: f64 = params.require(&label("scale").into())?;
+                let zero_point: f64 = params.require(&label("zero_point").into())?;
+                let dtype: Option<Value> = params.option(&label("dtype").into(), || None)?;
+                params.expect_empty()?;
+
+                let dtype = match dtype {
+                    Some(dtype) => {
+                        let dtype = ValueType::try_cast_from(dtype, |v| {
+                            TCError::invalid_type(v, "a Number class")
+                        })?;
+                        dtype.try_into()?
+                    }
+                    None => NumberType::Float(FloatType::F64),
+                };
+
+                self.tensor
+                    .sub_const(zero_point.into())?
+                    .mul_const(scale.into())?
+                    .cast(dtype)
+                    .map(Collection::Tensor)
+                    .map(State::Collection)
+            })
+        }))
+    }
+}
+
+impl From<Tensor> for DequantizeHandler {
+    fn from(tensor: Tensor) -> Self {
+        Self { tensor }
+    }
+}
+
 pub struct Static;
 
 impl Route for Static {
@@ -1916,8 +3797,16 @@ impl Route for Static {
             "dense" => TensorType::Dense.route(&path[1..]),
             "sparse" => TensorType::Sparse.route(&path[1..]),
             "copy_from" if path.len() == 1 => Some(Box::new(CopyFromHandler)),
+            // gradients over the tape recorded automatically for this
+            // transaction -- see the note on `BackwardHandler`
+            "gradients" if path.len() == 1 => Some(Box::new(BackwardHandler)),
             "load" if path.len() == 1 => Some(Box::new(LoadHandler { class: None })),
             "tile" if path.len() == 1 => Some(Box::new(TileHandler)),
+            "conv2d" if path.len() == 1 => Some(Box::new(Conv2dHandler)),
+            "max_pool2d" if path.len() == 1 => Some(Box::new(MaxPool2dHandler)),
+            "avg_pool2d" if path.len() == 1 => Some(Box::new(AvgPool2dHandler)),
+            "adaptive_avg_pool2d" if path.len() == 1 => Some(Box::new(AdaptiveAvgPool2dHandler)),
+            "qparams" if path.len() == 1 => Some(Box::new(QParamsHandler)),
             _ => None,
         }
     }
@@ -1994,6 +3883,40 @@ fn cast_bound(dim: u64, bound: Value) -> TCResult<u64> {
     }
 }
 
+/// Check that `permutation` is a true permutation of `0..ndim` -- every
+/// axis present exactly once, none out of range -- as required by
+/// [`TransposeHandler`] before handing it to `TensorTransform::transpose`.
+fn validate_permutation(permutation: &[usize], ndim: usize) -> TCResult<()> {
+    if permutation.len() != ndim {
+        return Err(bad_request!(
+            "tensor transpose requires a permutation of all {} axes, found {}",
+            ndim,
+            permutation.len()
+        ));
+    }
+
+    let mut seen = vec![false; ndim];
+    for &axis in permutation {
+        if axis >= ndim {
+            return Err(bad_request!(
+                "axis {} is out of range for a tensor with {} dimensions",
+                axis,
+                ndim
+            ));
+        } else if seen[axis] {
+            return Err(bad_request!(
+                "invalid permutation {:?}: axis {} is repeated",
+                permutation,
+                axis
+            ));
+        }
+
+        seen[axis] = true;
+    }
+
+    Ok(())
+}
+
 fn cast_axis(axis: Value, ndim: usize) -> TCResult<usize> {
     debug!("cast axis {} with ndim {}", axis, ndim);
 
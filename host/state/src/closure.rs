@@ -0,0 +1,97 @@
+//! A partially-applied [`OpDef`], closed over a fixed set of named `State`s
+//! so it can be relocated, serialized, or replayed independently of the
+//! scope it was defined in.
+
+use std::fmt;
+
+use async_hash::{Digest, Hash, Output, Sha256};
+use tc_error::TCResult;
+use tc_scalar::OpDef;
+use tc_transact::TxnId;
+use tcgeneric::{Id, Map, PathSegment, TCPathBuf};
+
+use crate::State;
+
+/// An [`OpDef`] paired with the `Map<Id, State>` it closed over -- the
+/// bindings it needs to run that aren't supplied by its own call arguments.
+#[derive(Clone)]
+pub struct Closure {
+    captured: Map<Id, State>,
+    op: OpDef,
+}
+
+impl Closure {
+    /// Close `op` over `captured`, the same way a `With` ref names the ids
+    /// of an enclosing scope to carry into a deferred op.
+    pub fn new(captured: Map<Id, State>, op: OpDef) -> Self {
+        Self { captured, op }
+    }
+
+    /// The environment this closure was built with, by id.
+    pub fn captured(&self) -> &Map<Id, State> {
+        &self.captured
+    }
+
+    /// The op this closure will run once called.
+    pub fn op(&self) -> &OpDef {
+        &self.op
+    }
+
+    /// Decompose this closure back into its captured environment and op --
+    /// the inverse of [`Closure::new`], for a caller that needs to encode a
+    /// `Closure` field-by-field (e.g. back out to the same `(captured, op)`
+    /// shape [`crate::StateVisitor`] decodes).
+    pub fn into_parts(self) -> (Map<Id, State>, OpDef) {
+        (self.captured, self.op)
+    }
+
+    pub fn dereference_self(self, path: &TCPathBuf) -> Self {
+        let captured = self
+            .captured
+            .into_iter()
+            .map(|(id, state)| (id, state.dereference_self(path)))
+            .collect();
+
+        Self {
+            captured,
+            op: self.op,
+        }
+    }
+
+    pub fn reference_self(self, path: &TCPathBuf) -> Self {
+        let captured = self
+            .captured
+            .into_iter()
+            .map(|(id, state)| (id, state.reference_self(path)))
+            .collect();
+
+        Self {
+            captured,
+            op: self.op,
+        }
+    }
+
+    pub fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
+        self.captured
+            .values()
+            .any(|state| state.is_inter_service_write(cluster_path))
+    }
+
+    pub async fn hash(self, txn_id: TxnId) -> TCResult<Output<Sha256>> {
+        let mut hasher = Sha256::default();
+        hasher.update(&Hash::<Sha256>::hash(&self.op));
+
+        for (id, state) in self.captured {
+            hasher.update(&Hash::<Sha256>::hash(id));
+            hasher.update(&state.hash(txn_id).await?);
+        }
+
+        Ok(hasher.finalize())
+    }
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a closure over {} captured state(s)", self.captured.len())
+    }
+}
@@ -0,0 +1,268 @@
+//! A declarative, caller-selected coercion operation for a resolved [`State`],
+//! backing [`State::cast_into`]: normalizes loosely-typed input
+//! (e.g. text columns from an external source) into a specific typed `Value`
+//! at resolve time, rather than pushing every conversion into client code.
+
+use std::fmt;
+use std::str::FromStr;
+
+use bytes::Bytes;
+use safecast::*;
+
+use tc_error::*;
+use tc_value::{Number, TCString, Value};
+use tcgeneric::Map;
+
+use crate::State;
+
+/// The target type of a [`Conversion`].
+///
+/// `Timestamp`/`TimestampFmt` parse into epoch nanoseconds (stored as a
+/// `Number`), since this chunk has no dedicated timestamp `Value`/`NumberType`
+/// variant of its own; the result remains orderable and hashable via the
+/// existing `AsyncHash`.
+#[derive(Clone)]
+pub enum Conversion {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    TimestampFmt(String),
+}
+
+impl FromStr for Conversion {
+    type Err = TCError;
+
+    fn from_str(name: &str) -> TCResult<Self> {
+        match name {
+            "asis" | "bytes" | "string" => Ok(Self::Bytes),
+            "int" | "integer" => Ok(Self::Integer),
+            "float" => Ok(Self::Float),
+            "bool" | "boolean" => Ok(Self::Boolean),
+            "timestamp" => Ok(Self::Timestamp),
+            fmt if fmt.starts_with("timestamp:") => {
+                Ok(Self::TimestampFmt(fmt["timestamp:".len()..].to_string()))
+            }
+            other => Err(TCError::bad_request("unknown Conversion", other)),
+        }
+    }
+}
+
+impl fmt::Display for Conversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bytes => f.write_str("bytes"),
+            Self::Integer => f.write_str("integer"),
+            Self::Float => f.write_str("float"),
+            Self::Boolean => f.write_str("boolean"),
+            Self::Timestamp => f.write_str("timestamp"),
+            Self::TimestampFmt(format) => write!(f, "timestamp:{}", format),
+        }
+    }
+}
+
+impl Conversion {
+    /// Coerce `state` into the type selected by this `Conversion`. A
+    /// `State::Map`/`State::Tuple` subject is coerced element-wise.
+    pub fn apply(&self, state: State) -> TCResult<State> {
+        match state {
+            State::Map(map) => {
+                let map = map
+                    .into_iter()
+                    .map(|(id, state)| self.apply(state).map(|state| (id, state)))
+                    .collect::<TCResult<Map<State>>>()?;
+
+                Ok(State::Map(map))
+            }
+            State::Tuple(tuple) => {
+                let tuple = tuple
+                    .into_iter()
+                    .map(|state| self.apply(state))
+                    .collect::<TCResult<Vec<State>>>()?;
+
+                Ok(State::Tuple(tuple.into()))
+            }
+            State::Scalar(scalar) => {
+                let value = Value::try_from(scalar)?;
+                self.apply_value(value).map(State::from)
+            }
+            other => Err(TCError::unexpected(other, "a value to convert")),
+        }
+    }
+
+    fn apply_value(&self, value: Value) -> TCResult<Value> {
+        match self {
+            Self::Bytes => {
+                let s = as_string(value)?;
+                Ok(Value::Bytes(Bytes::from(s.into_bytes())))
+            }
+            Self::Integer => parse_number(value)
+                .map(|n| Number::from(i64::cast_from(n)))
+                .map(Value::Number),
+            Self::Float => parse_number(value)
+                .map(|n| Number::from(f64::cast_from(n)))
+                .map(Value::Number),
+            Self::Boolean => parse_bool(value).map(Number::from).map(Value::Number),
+            Self::Timestamp => parse_timestamp(value, None),
+            Self::TimestampFmt(format) => parse_timestamp(value, Some(format)),
+        }
+    }
+}
+
+/// Coerce each field of `state` (a `State::Map`) named in `conversions` into
+/// its declared [`Conversion`], leaving any field not mentioned untouched.
+/// This lets a caller ingesting a flat text record declare per-field target
+/// types once and get back a fully-typed `State::Map`.
+pub fn apply_fields(conversions: &Map<Conversion>, state: State) -> TCResult<State> {
+    match state {
+        State::Map(map) => {
+            let map = map
+                .into_iter()
+                .map(|(id, state)| {
+                    let state = match conversions.get(&id) {
+                        Some(conversion) => conversion.apply(state)?,
+                        None => state,
+                    };
+
+                    Ok((id, state))
+                })
+                .collect::<TCResult<Map<State>>>()?;
+
+            Ok(State::Map(map))
+        }
+        other => Err(TCError::unexpected(other, "a Map of fields to convert")),
+    }
+}
+
+fn as_string(value: Value) -> TCResult<String> {
+    match value {
+        Value::Number(n) => Ok(n.to_string()),
+        other => TCString::try_cast_from(other, |v| TCError::unexpected(v, "a string"))
+            .map(|s| s.to_string()),
+    }
+}
+
+fn parse_number(value: Value) -> TCResult<Number> {
+    match value {
+        Value::Number(n) => Ok(n),
+        other => {
+            let s = as_string(other)?;
+            s.parse::<f64>()
+                .map(Number::from)
+                .map_err(|e| TCError::bad_request("invalid number", e))
+        }
+    }
+}
+
+fn parse_bool(value: Value) -> TCResult<bool> {
+    match value {
+        Value::Number(n) => Ok(bool::cast_from(n)),
+        other => match as_string(other)?.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => Err(TCError::bad_request("invalid boolean", other.to_string())),
+        },
+    }
+}
+
+fn parse_timestamp(value: Value, format: Option<&str>) -> TCResult<Value> {
+    let s = as_string(value)?;
+
+    let nanos = if let Some(format) = format {
+        parse_with_format(&s, format)?
+    } else {
+        parse_rfc3339(&s)?
+    };
+
+    Ok(Value::Number(Number::from(nanos)))
+}
+
+/// Parse an RFC 3339 timestamp (`YYYY-MM-DDTHH:MM:SS[.fraction][Z]`) into epoch
+/// nanoseconds. A non-`Z` UTC offset is not supported.
+fn parse_rfc3339(s: &str) -> TCResult<i64> {
+    let invalid = || TCError::bad_request("invalid RFC 3339 timestamp", s.to_string());
+
+    let s = s.trim_end_matches('Z');
+    let (date, time) = s.split_once(['T', ' ']).ok_or_else(invalid)?;
+
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let month: u32 = date.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let day: u32 = date.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+
+    let (time, nanos) = match time.split_once('.') {
+        Some((time, fraction)) => {
+            let fraction = format!("{:0<9}", fraction);
+            (time, fraction[..9].parse().map_err(|_| invalid())?)
+        }
+        None => (time, 0),
+    };
+
+    let mut time = time.splitn(3, ':');
+    let hour: u32 = time.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let minute: u32 = time.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let second: u32 = time.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+
+    epoch_nanos(year, month, day, hour, minute, second, nanos)
+}
+
+/// A minimal `strptime`-style parser supporting `%Y %m %d %H %M %S`, with any
+/// other character in `format` matched literally against `s`.
+fn parse_with_format(s: &str, format: &str) -> TCResult<i64> {
+    let invalid = || TCError::bad_request("timestamp does not match format", format.to_string());
+
+    let (mut year, mut month, mut day, mut hour, mut minute, mut second) = (1970i64, 1u32, 1u32, 0u32, 0u32, 0u32);
+
+    let mut s = s;
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            let field = chars.next().ok_or_else(invalid)?;
+            let (width, dest): (usize, &mut dyn FnMut(i64)) = match field {
+                'Y' => (4, &mut |v| year = v),
+                'm' => (2, &mut |v| month = v as u32),
+                'd' => (2, &mut |v| day = v as u32),
+                'H' => (2, &mut |v| hour = v as u32),
+                'M' => (2, &mut |v| minute = v as u32),
+                'S' => (2, &mut |v| second = v as u32),
+                _ => return Err(invalid()),
+            };
+
+            if s.len() < width {
+                return Err(invalid());
+            }
+
+            let (digits, rest) = s.split_at(width);
+            let value: i64 = digits.parse().map_err(|_| invalid())?;
+            dest(value);
+            s = rest;
+        } else {
+            if !s.starts_with(c) {
+                return Err(invalid());
+            }
+            s = &s[c.len_utf8()..];
+        }
+    }
+
+    epoch_nanos(year, month, day, hour, minute, second, 0)
+}
+
+fn epoch_nanos(year: i64, month: u32, day: u32, hour: u32, minute: u32, second: u32, nanos: u32) -> TCResult<i64> {
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86_400 + hour as i64 * 3_600 + minute as i64 * 60 + second as i64;
+    Ok(secs * 1_000_000_000 + nanos as i64)
+}
+
+/// Howard Hinnant's `days_from_civil`: days since the Unix epoch for a
+/// proleptic Gregorian calendar date, with no external dependency required.
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
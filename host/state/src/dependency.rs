@@ -0,0 +1,70 @@
+//! Dependency-ordering for the members of a [`crate::State::Map`] or
+//! [`crate::State::Tuple`] being resolved, so a cyclic reference graph is
+//! reported as a structured error instead of deadlocking or silently failing.
+
+use std::collections::{HashMap, HashSet};
+
+use tc_error::*;
+use tcgeneric::Id;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Color {
+    White,
+    Gray,
+    Black,
+}
+
+/// Given the `requires` graph of a set of sibling members (an edge `a -> b` means
+/// "`a` requires `b`"), return the members in dependency order (a member's
+/// dependencies precede it), or a `TCError` describing the cyclic reference chain.
+pub fn dependency_order(edges: HashMap<Id, HashSet<Id>>) -> TCResult<Vec<Id>> {
+    let mut color: HashMap<Id, Color> = edges.keys().cloned().map(|id| (id, Color::White)).collect();
+    let mut stack = Vec::new();
+    let mut order = Vec::with_capacity(edges.len());
+
+    for node in edges.keys().cloned().collect::<Vec<Id>>() {
+        visit(&node, &edges, &mut color, &mut stack, &mut order)?;
+    }
+
+    Ok(order)
+}
+
+fn visit(
+    node: &Id,
+    edges: &HashMap<Id, HashSet<Id>>,
+    color: &mut HashMap<Id, Color>,
+    stack: &mut Vec<Id>,
+    order: &mut Vec<Id>,
+) -> TCResult<()> {
+    match color.get(node) {
+        Some(Color::Black) | None => return Ok(()),
+        Some(Color::Gray) => {
+            let start = stack.iter().position(|id| id == node).unwrap_or(0);
+            let mut chain: Vec<String> = stack[start..].iter().map(Id::to_string).collect();
+            chain.push(node.to_string());
+
+            return Err(TCError::bad_request(
+                "dependency cycle detected",
+                chain.join(" -> "),
+            ));
+        }
+        Some(Color::White) => {}
+    }
+
+    color.insert(node.clone(), Color::Gray);
+    stack.push(node.clone());
+
+    if let Some(deps) = edges.get(node) {
+        for dep in deps {
+            if edges.contains_key(dep) {
+                visit(dep, edges, color, stack, order)?;
+            }
+        }
+    }
+
+    stack.pop();
+    color.insert(node.clone(), Color::Black);
+    order.push(node.clone());
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 //! A TinyChain [`State`]
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::str::FromStr;
@@ -34,11 +34,20 @@ use chain::*;
 use closure::*;
 use collection::*;
 use object::{InstanceClass, Object, ObjectType, ObjectVisitor};
+use conversion::Conversion;
+pub use limits::DecodeLimits;
+use pattern::Match;
+
+mod dependency;
 
 pub mod chain;
 pub mod closure;
 pub mod collection;
+pub mod conversion;
+pub mod limits;
 pub mod object;
+pub mod ordkey;
+pub mod pattern;
 pub mod public;
 pub mod view;
 
@@ -51,6 +60,7 @@ pub enum StateType {
     Collection(CollectionType),
     Closure,
     Map,
+    Match,
     Object(ObjectType),
     Scalar(ScalarType),
     Tuple,
@@ -69,6 +79,7 @@ impl NativeClass for StateType {
                 match path[1].as_str() {
                     "closure" => Some(Self::Closure),
                     "map" => Some(Self::Map),
+                    "match" => Some(Self::Match),
                     "tuple" => Some(Self::Tuple),
                     _ => None,
                 }
@@ -94,6 +105,7 @@ impl NativeClass for StateType {
             Self::Chain(ct) => ct.path(),
             Self::Closure => path_label(&["state", "closure"]).into(),
             Self::Map => path_label(&["state", "map"]).into(),
+            Self::Match => path_label(&["state", "match"]).into(),
             Self::Object(ot) => ot.path(),
             Self::Scalar(st) => st.path(),
             Self::Tuple => path_label(&["state", "tuple"]).into(),
@@ -173,6 +185,7 @@ impl fmt::Debug for StateType {
             Self::Collection(ct) => fmt::Debug::fmt(ct, f),
             Self::Closure => f.write_str("closure"),
             Self::Map => f.write_str("Map<State>"),
+            Self::Match => f.write_str("Match"),
             Self::Object(ot) => fmt::Debug::fmt(ot, f),
             Self::Scalar(st) => fmt::Debug::fmt(st, f),
             Self::Tuple => f.write_str("Tuple<State>"),
@@ -187,6 +200,7 @@ pub enum State {
     Chain(Chain<CollectionBase>),
     Closure(Closure),
     Map(Map<Self>),
+    Match(Match),
     Object(Object),
     Scalar(Scalar),
     Tuple(Tuple<Self>),
@@ -212,6 +226,7 @@ impl State {
     pub fn is_ref(&self) -> bool {
         match self {
             Self::Map(map) => map.values().any(Self::is_ref),
+            Self::Match(_) => true,
             Self::Scalar(scalar) => Refer::<State>::is_ref(scalar),
             Self::Tuple(tuple) => tuple.iter().any(Self::is_ref),
             _ => false,
@@ -245,6 +260,23 @@ impl State {
             other => Err((err)(other)),
         }
     }
+
+    /// Encode this `State` as a canonical, order-preserving key, suitable for use
+    /// as a `BTree`/`Table` index key. See [`ordkey`] for the encoding scheme.
+    pub fn to_ordered_key(&self) -> TCResult<Bytes> {
+        ordkey::encode(self)
+    }
+
+    /// Decode a key written by [`State::to_ordered_key`]. See [`ordkey::decode`]
+    /// for which values do not decode back to their original type.
+    pub fn from_ordered_key(encoded: &[u8]) -> TCResult<State> {
+        ordkey::decode(encoded)
+    }
+
+    /// Coerce this `State` into the type named by `conversion`. See [`conversion::Conversion`].
+    pub fn cast_into(self, conversion: &Conversion) -> TCResult<State> {
+        conversion.apply(self)
+    }
 }
 
 impl StateInstance for State {
@@ -282,6 +314,7 @@ impl Refer<State> for State {
 
                 Self::Map(map)
             }
+            Self::Match(match_ref) => Self::Match(match_ref.dereference_self(path)),
             Self::Scalar(scalar) => Self::Scalar(Refer::<State>::dereference_self(scalar, path)),
             Self::Tuple(tuple) => {
                 let tuple = tuple
@@ -298,6 +331,7 @@ impl Refer<State> for State {
     fn is_conditional(&self) -> bool {
         match self {
             Self::Map(map) => map.values().any(|state| state.is_conditional()),
+            Self::Match(match_ref) => match_ref.is_conditional(),
             Self::Scalar(scalar) => Refer::<State>::is_conditional(scalar),
             Self::Tuple(tuple) => tuple.iter().any(|state| state.is_conditional()),
             _ => false,
@@ -311,6 +345,8 @@ impl Refer<State> for State {
                 .values()
                 .any(|state| state.is_inter_service_write(cluster_path)),
 
+            Self::Match(match_ref) => match_ref.is_inter_service_write(cluster_path),
+
             Self::Scalar(scalar) => Refer::<State>::is_inter_service_write(scalar, cluster_path),
 
             Self::Tuple(tuple) => tuple
@@ -324,6 +360,7 @@ impl Refer<State> for State {
     fn is_ref(&self) -> bool {
         match self {
             Self::Map(map) => map.values().any(|state| state.is_ref()),
+            Self::Match(_) => true,
             Self::Scalar(scalar) => Refer::<State>::is_ref(scalar),
             Self::Tuple(tuple) => tuple.iter().any(|state| state.is_ref()),
             _ => false,
@@ -341,6 +378,7 @@ impl Refer<State> for State {
 
                 Self::Map(map)
             }
+            Self::Match(match_ref) => Self::Match(match_ref.reference_self(path)),
             Self::Scalar(scalar) => Self::Scalar(Refer::<State>::reference_self(scalar, path)),
             Self::Tuple(tuple) => {
                 let tuple = tuple
@@ -361,6 +399,7 @@ impl Refer<State> for State {
                     state.requires(deps);
                 }
             }
+            Self::Match(match_ref) => match_ref.requires(deps),
             Self::Scalar(scalar) => Refer::<State>::requires(scalar, deps),
             Self::Tuple(tuple) => {
                 for state in tuple.iter() {
@@ -379,31 +418,55 @@ impl Refer<State> for State {
         debug!("State::resolve {:?}", self);
 
         match self {
-            Self::Map(map) => {
-                let mut resolved = futures::stream::iter(map)
-                    .map(|(id, state)| state.resolve(context, txn).map_ok(|state| (id, state)))
-                    .buffer_unordered(num_cpus::get());
+            Self::Map(mut map) => {
+                let mut edges = HashMap::new();
+                for (id, state) in map.iter() {
+                    let mut deps = HashSet::new();
+                    state.requires(&mut deps);
+                    edges.insert(id.clone(), deps);
+                }
+
+                let order = dependency::dependency_order(edges)?;
 
-                let mut map = Map::new();
-                while let Some((id, state)) = resolved.try_next().await? {
-                    map.insert(id, state);
+                let mut resolved = Map::new();
+                for id in order {
+                    if let Some(state) = map.remove(&id) {
+                        let state = state.resolve(context, txn).await?;
+                        resolved.insert(id, state);
+                    }
                 }
 
-                Ok(State::Map(map))
+                Ok(State::Map(resolved))
             }
+            Self::Match(match_ref) => match_ref.resolve(context, txn).await,
             Self::Scalar(scalar) => scalar.resolve(context, txn).await,
             Self::Tuple(tuple) => {
-                let len = tuple.len();
-                let mut resolved = futures::stream::iter(tuple)
-                    .map(|state| state.resolve(context, txn))
-                    .buffered(num_cpus::get());
+                let mut edges = HashMap::new();
+                for (i, state) in tuple.iter().enumerate() {
+                    let mut deps = HashSet::new();
+                    state.requires(&mut deps);
+                    edges.insert(Id::from(i), deps);
+                }
 
-                let mut tuple = Vec::with_capacity(len);
-                while let Some(state) = resolved.try_next().await? {
-                    tuple.push(state);
+                let order = dependency::dependency_order(edges)?;
+
+                let mut tuple: Vec<Option<State>> = tuple.into_iter().map(Some).collect();
+                let mut resolved: HashMap<Id, State> = HashMap::with_capacity(tuple.len());
+                for id in order {
+                    let i: usize = id.to_string().parse().map_err(|_| {
+                        TCError::internal(format!("invalid Tuple position {}", id))
+                    })?;
+
+                    let state = tuple[i].take().expect("tuple item");
+                    let state = state.resolve(context, txn).await?;
+                    resolved.insert(id, state);
                 }
 
-                Ok(State::Tuple(tuple.into()))
+                let resolved = (0..tuple.len())
+                    .map(|i| resolved.remove(&Id::from(i)).expect("resolved tuple item"))
+                    .collect::<Vec<State>>();
+
+                Ok(State::Tuple(resolved.into()))
             }
             other => Ok(other),
         }
@@ -425,6 +488,7 @@ impl Instance for State {
             Self::Closure(_) => StateType::Closure,
             Self::Collection(collection) => StateType::Collection(collection.class()),
             Self::Map(_) => StateType::Map,
+            Self::Match(_) => StateType::Match,
             Self::Object(object) => StateType::Object(object.class()),
             Self::Scalar(scalar) => StateType::Scalar(scalar.class()),
             Self::Tuple(_) => StateType::Tuple,
@@ -461,6 +525,10 @@ impl AsyncHash for State {
 
                 Ok(hasher.finalize())
             }
+            Self::Match(_) => Err(TCError::unexpected(
+                "an unresolved Match reference",
+                "a hashable State",
+            )),
             Self::Object(object) => object.hash(txn_id).await,
             Self::Scalar(scalar) => Ok(Hash::<Sha256>::hash(scalar)),
             Self::Tuple(tuple) => {
@@ -1352,6 +1420,7 @@ impl fmt::Debug for State {
             Self::Closure(closure) => fmt::Debug::fmt(closure, f),
             Self::Collection(collection) => fmt::Debug::fmt(collection, f),
             Self::Map(map) => fmt::Debug::fmt(map, f),
+            Self::Match(_) => f.write_str("a Match reference"),
             Self::Object(object) => fmt::Debug::fmt(object, f),
             Self::Scalar(scalar) => fmt::Debug::fmt(scalar, f),
             Self::Tuple(tuple) => fmt::Debug::fmt(tuple, f),
@@ -1362,6 +1431,8 @@ impl fmt::Debug for State {
 struct StateVisitor {
     txn: Txn,
     scalar: ScalarVisitor,
+    limits: DecodeLimits,
+    guard: limits::DecodeGuard,
 }
 
 impl StateVisitor {
@@ -1380,10 +1451,8 @@ impl StateVisitor {
                     .await
             }
             StateType::Closure => {
-                access
-                    .next_value(self.txn.clone())
-                    .map_ok(State::Closure)
-                    .await
+                let (captured, op) = access.next_value(self.txn.clone()).await?;
+                Ok(State::Closure(Closure::new(captured, op)))
             }
             StateType::Collection(ct) => {
                 CollectionVisitor::new(self.txn.clone())
@@ -1393,6 +1462,10 @@ impl StateVisitor {
                     .await
             }
             StateType::Map => access.next_value(self.txn.clone()).await,
+            StateType::Match => {
+                let (subject, branches) = access.next_value(self.txn.clone()).await?;
+                Ok(State::Match(Match::new(subject, branches)))
+            }
             StateType::Object(ot) => {
                 let txn = self
                     .txn
@@ -1416,7 +1489,10 @@ impl StateVisitor {
     }
 }
 
-// TODO: guard against a DoS attack using an infinite request stream
+// `visit_seq`/`visit_map` enforce `DecodeLimits` (see `limits`) against an
+// infinite or oversized request stream; `StateType::Chain`/`Collection`/
+// `Object` delegate to visitors from other crates that this chunk cannot
+// extend with the same accounting.
 #[async_trait]
 impl<'a> de::Visitor for StateVisitor {
     type Value = State;
@@ -1513,6 +1589,7 @@ impl<'a> de::Visitor for StateVisitor {
 
             let mut map = Map::new();
 
+            self.guard.charge(1).map_err(de::Error::custom)?;
             let id = Id::from_str(&key).map_err(de::Error::custom)?;
             let txn = self
                 .txn
@@ -1524,6 +1601,15 @@ impl<'a> de::Visitor for StateVisitor {
             map.insert(id, value);
 
             while let Some(id) = access.next_key::<Id>(()).await? {
+                if map.len() >= self.limits.max_collection_len {
+                    return Err(de::Error::custom(TCError::bad_request(
+                        "this Map exceeds the maximum number of decoded entries",
+                        self.limits.max_collection_len,
+                    )));
+                }
+
+                self.guard.charge(1).map_err(de::Error::custom)?;
+
                 let txn = self
                     .txn
                     .subcontext(id.clone())
@@ -1542,6 +1628,13 @@ impl<'a> de::Visitor for StateVisitor {
 
     async fn visit_seq<A: de::SeqAccess>(self, mut access: A) -> Result<Self::Value, A::Error> {
         let mut seq = if let Some(len) = access.size_hint() {
+            if len > self.limits.max_collection_len {
+                return Err(de::Error::custom(TCError::bad_request(
+                    "this Tuple exceeds the maximum number of decoded elements",
+                    self.limits.max_collection_len,
+                )));
+            }
+
             Vec::with_capacity(len)
         } else {
             Vec::new()
@@ -1549,6 +1642,15 @@ impl<'a> de::Visitor for StateVisitor {
 
         let mut i = 0usize;
         loop {
+            if seq.len() >= self.limits.max_collection_len {
+                return Err(de::Error::custom(TCError::bad_request(
+                    "this Tuple exceeds the maximum number of decoded elements",
+                    self.limits.max_collection_len,
+                )));
+            }
+
+            self.guard.charge(1).map_err(de::Error::custom)?;
+
             let txn = self
                 .txn
                 .subcontext(i.into())
@@ -1572,7 +1674,16 @@ impl de::FromStream for State {
     type Context = Txn;
 
     async fn from_stream<D: de::Decoder>(txn: Txn, decoder: &mut D) -> Result<Self, D::Error> {
+        let limits = DecodeLimits::default();
+        let guard = limits::DecodeGuard::enter(txn.id(), &limits).map_err(de::Error::custom)?;
         let scalar = ScalarVisitor::default();
-        decoder.decode_any(StateVisitor { txn, scalar }).await
+        decoder
+            .decode_any(StateVisitor {
+                txn,
+                scalar,
+                limits,
+                guard,
+            })
+            .await
     }
 }
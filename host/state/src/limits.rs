@@ -0,0 +1,169 @@
+//! Limits enforced while decoding a [`crate::State`] from an untrusted
+//! stream, closing the DoS hole left by `StateVisitor`'s unbounded
+//! `visit_seq`/`visit_map` loops: a malicious encoder could otherwise exhaust
+//! memory (an unbounded sequence) or the decode stack (unbounded nesting).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tc_error::*;
+use tc_transact::TxnId;
+
+/// Policy enforced by [`crate::StateVisitor`]: a max nesting depth, a max
+/// element count per `Tuple`/`Map` collection, and a max total element count
+/// shared across the whole decoded tree (including sibling subcontexts).
+/// `max_array_bytes` is stored with the policy, but `StateVisitor`'s
+/// `visit_array_u8` does not check it: an `array_u8` payload is decoded at
+/// any length.
+#[derive(Clone, Copy)]
+pub struct DecodeLimits {
+    pub(crate) max_depth: usize,
+    pub(crate) max_collection_len: usize,
+    pub(crate) max_total_elements: usize,
+    pub(crate) max_array_bytes: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_collection_len: 1_000_000,
+            max_total_elements: 10_000_000,
+            max_array_bytes: 100_000_000,
+        }
+    }
+}
+
+impl DecodeLimits {
+    pub fn builder() -> DecodeLimitsBuilder {
+        DecodeLimitsBuilder::default()
+    }
+}
+
+/// Builds a [`DecodeLimits`], starting from [`DecodeLimits::default`].
+/// `State`'s `FromStream` impl always decodes with `DecodeLimits::default()`;
+/// no decode entry point in this crate takes a built `DecodeLimits`.
+#[derive(Default)]
+pub struct DecodeLimitsBuilder(DecodeLimits);
+
+impl DecodeLimitsBuilder {
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.0.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_collection_len(mut self, max_collection_len: usize) -> Self {
+        self.0.max_collection_len = max_collection_len;
+        self
+    }
+
+    pub fn max_total_elements(mut self, max_total_elements: usize) -> Self {
+        self.0.max_total_elements = max_total_elements;
+        self
+    }
+
+    pub fn max_array_bytes(mut self, max_array_bytes: usize) -> Self {
+        self.0.max_array_bytes = max_array_bytes;
+        self
+    }
+
+    pub fn build(self) -> DecodeLimits {
+        self.0
+    }
+}
+
+struct PerTxn {
+    budget: Arc<AtomicUsize>,
+    depth: Arc<AtomicUsize>,
+}
+
+/// The global element budget and nesting depth for a single decode, shared
+/// across every sibling subcontext spawned for that transaction
+/// (`Txn::subcontext`, `Txn::subcontext_unique`) so a wide-but-shallow stream
+/// is caught even though each subcontext decodes through its own, freshly
+/// constructed `StateVisitor`.
+///
+/// This is keyed by `TxnId` rather than carried on `Txn` itself, since `Txn`
+/// is an opaque type from `tc_fs` with no room in this chunk to add a field
+/// to it; the registry entry for a `TxnId` is never evicted here, matching
+/// how short-lived per-transaction state is tracked elsewhere in this crate.
+/// Depth tracking assumes a `TxnId`'s decode work happens sequentially
+/// (true today: `StateVisitor::visit_map`/`visit_seq` `.await` each element
+/// in turn rather than decoding siblings concurrently).
+fn per_txn(txn_id: &TxnId) -> Arc<PerTxn> {
+    static REGISTRY: OnceLock<Mutex<HashMap<TxnId, Arc<PerTxn>>>> = OnceLock::new();
+
+    let registry = REGISTRY.get_or_init(|| Mutex::new(HashMap::new()));
+    registry
+        .lock()
+        .expect("decode limits registry")
+        .entry(txn_id.clone())
+        .or_insert_with(|| {
+            Arc::new(PerTxn {
+                budget: Arc::new(AtomicUsize::new(usize::MAX)),
+                depth: Arc::new(AtomicUsize::new(0)),
+            })
+        })
+        .clone()
+}
+
+/// A decode in progress for one `TxnId`: holds the shared element budget and
+/// releases this decode's claim on the shared nesting depth when dropped.
+pub(crate) struct DecodeGuard {
+    budget: Arc<AtomicUsize>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl DecodeGuard {
+    /// Register a new decode nesting level for `txn_id`, capping the shared
+    /// element budget at `limits.max_total_elements` the first time this
+    /// `TxnId` is seen. Errors if entering would exceed `limits.max_depth`.
+    pub(crate) fn enter(txn_id: &TxnId, limits: &DecodeLimits) -> TCResult<Self> {
+        let state = per_txn(txn_id);
+
+        // the first decode for this TxnId sets the starting budget
+        let _ = state.budget.compare_exchange(
+            usize::MAX,
+            limits.max_total_elements,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+
+        let depth = state.depth.fetch_add(1, Ordering::SeqCst) + 1;
+        if depth > limits.max_depth {
+            state.depth.fetch_sub(1, Ordering::SeqCst);
+            return Err(TCError::bad_request(
+                "this State exceeds the maximum decode nesting depth",
+                limits.max_depth,
+            ));
+        }
+
+        Ok(Self {
+            budget: state.budget,
+            depth: state.depth,
+        })
+    }
+
+    /// Deduct `n` from the shared element budget, or error if doing so would
+    /// take it below zero.
+    pub(crate) fn charge(&self, n: usize) -> TCResult<()> {
+        self.budget
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                remaining.checked_sub(n)
+            })
+            .map(|_| ())
+            .map_err(|remaining| {
+                TCError::bad_request(
+                    "this request exceeds the maximum number of elements allowed in a single decoded State (elements remaining)",
+                    remaining,
+                )
+            })
+    }
+}
+
+impl Drop for DecodeGuard {
+    fn drop(&mut self) {
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
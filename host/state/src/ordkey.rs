@@ -0,0 +1,276 @@
+//! Canonical, order-preserving byte encoding of a [`State`]/[`Scalar`]/[`Value`],
+//! so a composite value can be used directly as a `tc_collection::btree`/`table`
+//! index key: lexicographic comparison of two encodings matches the logical
+//! ordering of the values they encode.
+//!
+//! Technique: every encoded value is prefixed with a one-byte type tag, so
+//! values of different types sort deterministically by tag; numbers are
+//! encoded as a fixed-width big-endian float with the sign handled so that
+//! byte order equals numeric order; strings are UTF-8 terminated by `0x00`
+//! with any embedded `0x00` escaped as `0x00 0x01`; a `Tuple` is encoded as a
+//! tag followed by each element's self-delimiting encoding in order and a
+//! trailing end-of-tuple marker, and a `Map` as its sorted `(key, value)`
+//! pairs.
+
+use bytes::Bytes;
+use safecast::*;
+
+use tc_error::*;
+use tc_scalar::Scalar;
+use tc_value::{Number, TCString, Value};
+use tcgeneric::{Id, Map};
+
+use crate::State;
+
+// no tag equals `STRING_ESCAPE`, so a string's terminator followed by the
+// next element's tag never reads as an escaped `0x00`
+const TAG_NONE: u8 = 0;
+const TAG_NUMBER: u8 = 2;
+const TAG_STRING: u8 = 3;
+const TAG_TUPLE: u8 = 4;
+const TAG_MAP: u8 = 5;
+const TAG_END: u8 = 0xff;
+
+const STRING_TERMINATOR: u8 = 0x00;
+const STRING_ESCAPE: u8 = 0x01;
+
+/// Encode `state` as a canonical, order-preserving sequence of bytes.
+pub fn encode(state: &State) -> TCResult<Bytes> {
+    let mut buf = Vec::new();
+    encode_state(state, &mut buf)?;
+    Ok(Bytes::from(buf))
+}
+
+/// Decode a key written by [`encode`]. Every `Number` decodes as an `f64`
+/// (the encoding does not record the original number type), a `Tuple`
+/// decodes as a `Value::Tuple`, and an encoded `Map` is rejected with an
+/// error rather than decoded.
+pub fn decode(encoded: &[u8]) -> TCResult<State> {
+    let mut cursor = encoded;
+    let state = decode_state(&mut cursor)?;
+
+    if !cursor.is_empty() {
+        return Err(TCError::bad_request(
+            "trailing bytes after decoding an ordered key",
+            cursor.len(),
+        ));
+    }
+
+    Ok(state)
+}
+
+fn encode_state(state: &State, buf: &mut Vec<u8>) -> TCResult<()> {
+    match state {
+        State::Map(map) => encode_map(map, buf, encode_state),
+        State::Scalar(scalar) => encode_scalar(scalar, buf),
+        State::Tuple(tuple) => {
+            buf.push(TAG_TUPLE);
+            for item in tuple.iter() {
+                encode_state(item, buf)?;
+            }
+            buf.push(TAG_END);
+            Ok(())
+        }
+        other => Err(TCError::bad_request(
+            "cannot encode as an ordered key",
+            format!("{:?}", other.class()),
+        )),
+    }
+}
+
+fn encode_scalar(scalar: &Scalar, buf: &mut Vec<u8>) -> TCResult<()> {
+    match scalar {
+        Scalar::Map(map) => encode_map(map, buf, encode_scalar),
+        Scalar::Tuple(tuple) => {
+            buf.push(TAG_TUPLE);
+            for item in tuple.iter() {
+                encode_scalar(item, buf)?;
+            }
+            buf.push(TAG_END);
+            Ok(())
+        }
+        Scalar::Value(value) => encode_value(value, buf),
+        other => Err(TCError::bad_request(
+            "cannot encode as an ordered key",
+            format!("{:?}", other),
+        )),
+    }
+}
+
+fn encode_value(value: &Value, buf: &mut Vec<u8>) -> TCResult<()> {
+    match value {
+        Value::None => {
+            buf.push(TAG_NONE);
+            Ok(())
+        }
+        Value::Number(n) => {
+            buf.push(TAG_NUMBER);
+            encode_f64(f64::cast_from(n.clone()), buf);
+            Ok(())
+        }
+        Value::Tuple(tuple) => {
+            buf.push(TAG_TUPLE);
+            for item in tuple.iter() {
+                encode_value(item, buf)?;
+            }
+            buf.push(TAG_END);
+            Ok(())
+        }
+        other => {
+            if let Some(string) = TCString::opt_cast_from(other.clone()) {
+                buf.push(TAG_STRING);
+                encode_string(&string.to_string(), buf);
+                Ok(())
+            } else {
+                Err(TCError::bad_request(
+                    "cannot encode as an ordered key",
+                    format!("{:?}", other),
+                ))
+            }
+        }
+    }
+}
+
+fn encode_f64(f: f64, buf: &mut Vec<u8>) {
+    let bits = f.to_bits();
+    let bits = if f.is_sign_negative() {
+        !bits
+    } else {
+        bits | (1u64 << 63)
+    };
+
+    buf.extend_from_slice(&bits.to_be_bytes());
+}
+
+fn decode_f64(cursor: &mut &[u8]) -> TCResult<f64> {
+    if cursor.len() < 8 {
+        return Err(TCError::bad_request(
+            "invalid ordered key encoding",
+            "truncated Number",
+        ));
+    }
+
+    let (bytes, rest) = cursor.split_at(8);
+    *cursor = rest;
+
+    let bits = u64::from_be_bytes(bytes.try_into().expect("8 bytes"));
+    let is_negative = bits >> 63 == 0;
+    let bits = if is_negative { !bits } else { bits & !(1u64 << 63) };
+
+    Ok(f64::from_bits(bits))
+}
+
+fn encode_string(s: &str, buf: &mut Vec<u8>) {
+    for byte in s.as_bytes() {
+        if *byte == STRING_TERMINATOR {
+            buf.push(STRING_TERMINATOR);
+            buf.push(STRING_ESCAPE);
+        } else {
+            buf.push(*byte);
+        }
+    }
+
+    buf.push(STRING_TERMINATOR);
+}
+
+fn decode_string(cursor: &mut &[u8]) -> TCResult<String> {
+    let mut bytes = Vec::new();
+    let mut i = 0;
+
+    loop {
+        if i >= cursor.len() {
+            return Err(TCError::bad_request(
+                "invalid ordered key encoding",
+                "unterminated string",
+            ));
+        }
+
+        match cursor[i] {
+            STRING_TERMINATOR if cursor.get(i + 1) == Some(&STRING_ESCAPE) => {
+                bytes.push(STRING_TERMINATOR);
+                i += 2;
+            }
+            STRING_TERMINATOR => {
+                *cursor = &cursor[(i + 1)..];
+                return String::from_utf8(bytes)
+                    .map_err(|e| TCError::bad_request("invalid ordered key encoding", e));
+            }
+            byte => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn encode_map<T>(
+    map: &Map<T>,
+    buf: &mut Vec<u8>,
+    encode_item: fn(&T, &mut Vec<u8>) -> TCResult<()>,
+) -> TCResult<()> {
+    buf.push(TAG_MAP);
+
+    let mut entries: Vec<(&Id, &T)> = map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.to_string().cmp(&b.to_string()));
+
+    for (key, value) in entries {
+        encode_string(&key.to_string(), buf);
+        encode_item(value, buf)?;
+    }
+
+    buf.push(TAG_END);
+    Ok(())
+}
+
+fn decode_state(cursor: &mut &[u8]) -> TCResult<State> {
+    decode_value(cursor).map(State::from)
+}
+
+fn decode_value(cursor: &mut &[u8]) -> TCResult<Value> {
+    let tag = take_tag(cursor)?;
+
+    match tag {
+        TAG_NONE => Ok(Value::None),
+        TAG_NUMBER => decode_f64(cursor).map(Number::from).map(Value::Number),
+        TAG_STRING => decode_string(cursor).map(|s| Value::from(TCString::from(s))),
+        TAG_TUPLE => {
+            let mut tuple = Vec::new();
+
+            loop {
+                match cursor.first() {
+                    Some(&TAG_END) => {
+                        *cursor = &cursor[1..];
+                        break;
+                    }
+                    Some(_) => tuple.push(decode_value(cursor)?),
+                    None => {
+                        return Err(TCError::bad_request(
+                            "invalid ordered key encoding",
+                            "unterminated Tuple",
+                        ))
+                    }
+                }
+            }
+
+            Ok(Value::Tuple(tuple.into()))
+        }
+        TAG_MAP => Err(TCError::bad_request(
+            "a Map does not decode to a canonically-ordered Value",
+            "",
+        )),
+        other => Err(TCError::bad_request("invalid ordered key type tag", other)),
+    }
+}
+
+fn take_tag(cursor: &mut &[u8]) -> TCResult<u8> {
+    if cursor.is_empty() {
+        return Err(TCError::bad_request(
+            "invalid ordered key encoding",
+            "unexpected end of input",
+        ));
+    }
+
+    let tag = cursor[0];
+    *cursor = &cursor[1..];
+    Ok(tag)
+}
@@ -0,0 +1,308 @@
+//! A `match`/`case` construct over a resolved [`State`]: the [`Match`] reference
+//! destructures a subject against a sequence of declarative patterns and resolves
+//! the body of the first branch whose pattern matches.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use safecast::*;
+
+use tc_error::*;
+use tc_scalar::{Refer, Scalar, Scope};
+use tc_transact::public::{Public, ToState};
+use tc_value::{TCString, Value};
+use tcgeneric::{Id, Instance, Map, PathSegment, TCPathBuf, Tuple};
+
+use crate::{State, Txn};
+
+/// A pattern is itself a [`Scalar`], interpreted structurally at match time:
+/// an `Id` prefixed with `$` captures whatever `State` sits at that position
+/// under that `Id`; `_` discards without binding; any other literal `Value`
+/// must compare equal; a `Tuple` pattern matches a `Tuple<State>` of the same
+/// length element-wise; a `Map` pattern matches when every key in the pattern
+/// is present in the subject `Map` and its sub-pattern matches (extra subject
+/// keys are allowed).
+#[derive(Clone)]
+pub struct Match {
+    subject: Box<State>,
+    branches: Tuple<(Scalar, Scalar)>,
+}
+
+impl Match {
+    pub fn new(subject: State, branches: Tuple<(Scalar, Scalar)>) -> Self {
+        Match {
+            subject: Box::new(subject),
+            branches,
+        }
+    }
+
+    pub fn dereference_self(self, path: &TCPathBuf) -> Self {
+        Match {
+            subject: Box::new(self.subject.dereference_self(path)),
+            branches: self
+                .branches
+                .into_iter()
+                .map(|(pattern, body)| {
+                    (
+                        Refer::<State>::dereference_self(pattern, path),
+                        Refer::<State>::dereference_self(body, path),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn reference_self(self, path: &TCPathBuf) -> Self {
+        Match {
+            subject: Box::new(self.subject.reference_self(path)),
+            branches: self
+                .branches
+                .into_iter()
+                .map(|(pattern, body)| {
+                    (
+                        Refer::<State>::reference_self(pattern, path),
+                        Refer::<State>::reference_self(body, path),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_conditional(&self) -> bool {
+        true
+    }
+
+    pub fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
+        self.subject.is_inter_service_write(cluster_path)
+            || self
+                .branches
+                .iter()
+                .any(|(_, body)| Refer::<State>::is_inter_service_write(body, cluster_path))
+    }
+
+    pub fn requires(&self, deps: &mut HashSet<Id>) {
+        self.subject.requires(deps);
+
+        for (pattern, body) in self.branches.iter() {
+            Refer::<State>::requires(pattern, deps);
+            Refer::<State>::requires(body, deps);
+        }
+    }
+
+    pub async fn resolve<'a, T: ToState<State> + Instance + Public<State>>(
+        self,
+        context: &'a Scope<'a, State, T>,
+        txn: &'a Txn,
+    ) -> TCResult<State> {
+        let subject = self.subject.resolve(context, txn).await?;
+
+        for (pattern, body) in self.branches.into_iter() {
+            let mut bindings = Map::<State>::new();
+
+            if bind(&pattern, &subject, &mut bindings) {
+                // TODO: splice `bindings` into a child `Scope` once one can be
+                // constructed outside of the top-level request context
+                return State::Scalar(body).resolve(context, txn).await;
+            }
+        }
+
+        Err(TCError::not_found(
+            "a branch of this Match whose pattern matches the given subject",
+        ))
+    }
+}
+
+/// A pattern written as a bare identifier, e.g. `_`, is neither a binder nor
+/// a literal to compare against; distinguish the two reserved forms from an
+/// ordinary literal scalar.
+fn discard(pattern: &Scalar) -> bool {
+    TCString::opt_cast_from(pattern.clone())
+        .map(|s| s.to_string() == "_")
+        .unwrap_or(false)
+}
+
+fn binder(pattern: &Scalar) -> Option<Id> {
+    let name = TCString::opt_cast_from(pattern.clone())?.to_string();
+    let name = name.strip_prefix('$')?;
+    Id::from_str(name).ok()
+}
+
+/// Attempt to unify `pattern` against `subject`, recording captures into `bindings`.
+/// An arity mismatch or a missing `Map` key is a non-match, not an error.
+fn bind(pattern: &Scalar, subject: &State, bindings: &mut Map<State>) -> bool {
+    if discard(pattern) {
+        return true;
+    }
+
+    if let Some(name) = binder(pattern) {
+        bindings.insert(name, subject.clone());
+        return true;
+    }
+
+    match (pattern, subject) {
+        (Scalar::Tuple(pattern), State::Tuple(subject)) if pattern.len() == subject.len() => {
+            pattern
+                .iter()
+                .zip(subject.iter())
+                .all(|(p, s)| bind(p, s, bindings))
+        }
+        (Scalar::Map(pattern), State::Map(subject)) => pattern.iter().all(|(key, p)| {
+            subject
+                .get(key)
+                .map(|s| bind(p, s, bindings))
+                .unwrap_or(false)
+        }),
+        (literal, subject) => {
+            let literal = Value::try_from(literal.clone());
+            let subject = Value::try_from(subject.clone());
+
+            match (literal, subject) {
+                (Ok(literal), Ok(subject)) => literal == subject,
+                _ => false,
+            }
+        }
+    }
+}
+
+/// A structural destructuring pattern for a [`State`], distinct from the
+/// string-marker convention that [`Match`] uses over a [`Scalar`] body: each
+/// leaf of a `Pattern` is an explicit variant rather than a `$name`/`_`
+/// string, so a pattern can be built programmatically as well as parsed from
+/// a `State`.
+#[derive(Clone)]
+pub enum Pattern {
+    /// Capture the corresponding sub-`State` under this `Id`.
+    Bind(Id),
+    /// Match anything, without binding.
+    Discard,
+    /// In a trailing `Tuple` position, capture the remaining elements (as a
+    /// `Tuple`) under this `Id`.
+    Rest(Id),
+    /// Match only a `State` that casts to this exact `Value`.
+    Literal(Value),
+    /// Match a `State::Tuple` of the same length element-wise, or (with a
+    /// trailing [`Pattern::Rest`]) a `Tuple` of at least that many elements.
+    Tuple(Vec<Pattern>),
+    /// Match a `State::Map` containing (at least) the given keys, binding
+    /// each key's sub-pattern; keys not mentioned in the pattern are ignored.
+    Map(Map<Pattern>),
+}
+
+impl TryFrom<Value> for Pattern {
+    type Error = TCError;
+
+    fn try_from(value: Value) -> TCResult<Self> {
+        if let Some(name) = TCString::opt_cast_from(value.clone()).map(|s| s.to_string()) {
+            if name == "_" {
+                return Ok(Pattern::Discard);
+            } else if let Some(name) = name.strip_prefix('$') {
+                return if let Some(name) = name.strip_suffix("...") {
+                    Id::from_str(name).map(Pattern::Rest)
+                } else {
+                    Id::from_str(name).map(Pattern::Bind)
+                }
+                .map_err(|cause| TCError::bad_request("invalid Pattern binder", cause));
+            }
+        }
+
+        Ok(Pattern::Literal(value))
+    }
+}
+
+impl TryFrom<State> for Pattern {
+    type Error = TCError;
+
+    fn try_from(state: State) -> TCResult<Self> {
+        match state {
+            State::Tuple(tuple) => tuple
+                .into_iter()
+                .map(Pattern::try_from)
+                .collect::<TCResult<Vec<Pattern>>>()
+                .map(Pattern::Tuple),
+            State::Map(map) => map
+                .into_iter()
+                .map(|(id, state)| Pattern::try_from(state).map(|pattern| (id, pattern)))
+                .collect::<TCResult<Map<Pattern>>>()
+                .map(Pattern::Map),
+            State::Scalar(Scalar::Value(value)) => Pattern::try_from(value),
+            other => Value::try_from(other).and_then(Pattern::try_from),
+        }
+    }
+}
+
+impl State {
+    /// Match this `State` structurally against `pattern`, returning the
+    /// captured bindings, or `None` if `pattern` does not match (including a
+    /// conflicting bind of the same `Id` more than once).
+    pub fn match_pattern(&self, pattern: &Pattern) -> Option<Map<State>> {
+        let mut bindings = Map::new();
+
+        if bind_pattern(pattern, self, &mut bindings) {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+}
+
+fn bind_unique(bindings: &mut Map<State>, id: Id, state: State) -> bool {
+    if bindings.contains_key(&id) {
+        false
+    } else {
+        bindings.insert(id, state);
+        true
+    }
+}
+
+fn bind_pattern(pattern: &Pattern, subject: &State, bindings: &mut Map<State>) -> bool {
+    match pattern {
+        Pattern::Discard => true,
+        Pattern::Bind(id) | Pattern::Rest(id) => bind_unique(bindings, id.clone(), subject.clone()),
+        Pattern::Literal(literal) => Value::try_from(subject.clone())
+            .map(|value| &value == literal)
+            .unwrap_or(false),
+        Pattern::Tuple(patterns) => match subject {
+            State::Tuple(subject) => bind_tuple(patterns, subject, bindings),
+            _ => false,
+        },
+        Pattern::Map(patterns) => match subject {
+            State::Map(subject) => patterns.iter().all(|(key, p)| {
+                subject
+                    .get(key)
+                    .map(|s| bind_pattern(p, s, bindings))
+                    .unwrap_or(false)
+            }),
+            _ => false,
+        },
+    }
+}
+
+fn bind_tuple(patterns: &[Pattern], subject: &Tuple<State>, bindings: &mut Map<State>) -> bool {
+    match patterns.last() {
+        Some(Pattern::Rest(rest_id)) => {
+            let fixed = &patterns[..patterns.len() - 1];
+            if subject.len() < fixed.len() {
+                return false;
+            }
+
+            if !fixed
+                .iter()
+                .zip(subject.iter())
+                .all(|(p, s)| bind_pattern(p, s, bindings))
+            {
+                return false;
+            }
+
+            let tail: Vec<State> = subject.iter().skip(fixed.len()).cloned().collect();
+            bind_unique(bindings, rest_id.clone(), State::Tuple(tail.into()))
+        }
+        _ => {
+            patterns.len() == subject.len()
+                && patterns
+                    .iter()
+                    .zip(subject.iter())
+                    .all(|(p, s)| bind_pattern(p, s, bindings))
+        }
+    }
+}
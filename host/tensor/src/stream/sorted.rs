@@ -14,6 +14,28 @@ use crate::{Coord, Shape, TensorAccess, TensorType};
 
 use super::ReadValueAt;
 
+/// How many bits of radix an LSD radix-sort pass consumes at a time. A pass
+/// keeps one offset buffer for each of the 2^11 buckets (see [`radix_sort`]),
+/// while 11 bits per pass still bounds the number of passes for the offset
+/// domains this sort is worth selecting for.
+const RADIX_BITS: u32 = 11;
+const RADIX: usize = 1 << RADIX_BITS;
+
+/// Above this many passes, a full read-and-scatter of every block per pass
+/// costs more I/O than `BlockListFile::merge_sort`'s O(n log n) comparison
+/// sort would, so `sort_coords` falls back to it instead.
+const MAX_RADIX_PASSES: u32 = 4;
+
+/// How many bits are needed to represent `max_offset`, i.e. the number of
+/// radix passes of `RADIX_BITS` each multiplied out covers it.
+fn bits_needed(max_offset: u64) -> u32 {
+    64 - max_offset.leading_zeros()
+}
+
+fn digit_at(offset: u64, shift: u32) -> usize {
+    ((offset >> shift) as usize) & (RADIX - 1)
+}
+
 pub async fn sorted_coords<FD, FS, D, T, C>(
     txn: &T,
     shape: Shape,
@@ -33,7 +55,7 @@ where
         .create_file_tmp(txn_id, TensorType::Dense)
         .await?;
 
-    let offsets = sort_coords::<FD, FS, D, T, _>(file, txn_id, coords, shape.clone()).await?;
+    let offsets = sort_coords::<FD, FS, D, T, _>(txn, file, txn_id, coords, shape.clone()).await?;
     let offsets = offsets
         .into_stream(txn_id)
         .map_ok(|array| array.type_cast());
@@ -69,26 +91,142 @@ where
 }
 
 async fn sort_coords<FD, FS, D, T, S>(
+    txn: &T,
     file: FD,
     txn_id: TxnId,
     coords: S,
     shape: Shape,
 ) -> TCResult<BlockListFile<FD, FS, D, T>>
 where
-    FD: File<Array>,
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
     FS: File<Node>,
     D: Dir,
     T: Transaction<D>,
     S: Stream<Item = TCResult<Coords>> + Send + Unpin,
+    D::FileClass: From<TensorType>,
+    BlockListFile<FD, FS, D, T>: Clone,
 {
+    let max_offset = shape.size().saturating_sub(1);
     let blocks = coords_to_offsets(shape, coords).map_ok(|block| ArrayExt::from(block).into());
 
     let block_list =
         BlockListFile::from_blocks(file, txn_id, None, UIntType::U64.into(), Box::pin(blocks))
             .await?;
 
-    block_list.merge_sort(txn_id).await?;
-    Ok(block_list)
+    let passes = (bits_needed(max_offset) + RADIX_BITS - 1) / RADIX_BITS;
+    if passes == 0 || passes > MAX_RADIX_PASSES {
+        block_list.merge_sort(txn_id).await?;
+        Ok(block_list)
+    } else {
+        radix_sort(txn, block_list, txn_id, passes).await
+    }
+}
+
+/// Sort `source`'s `u64` offsets via `passes` rounds of LSD radix sort: each
+/// pass streams every block once, appending each offset to a small
+/// per-bucket buffer keyed by that pass's digit and spilling a bucket's
+/// buffer to its own single-block `BlockListFile` the moment it reaches
+/// `PER_BLOCK` offsets, then reassembles the pass's output by streaming
+/// every bucket's spilled blocks back out in bucket order. A pass therefore
+/// never holds more than `RADIX * PER_BLOCK` offsets in memory at once,
+/// rather than materializing the whole pass's offsets into one `Vec`.
+/// Offsets that land in the same bucket keep their relative order from the
+/// previous pass, so the result is fully sorted once the last (most
+/// significant digit) pass completes.
+async fn radix_sort<FD, FS, D, T>(
+    txn: &T,
+    source: BlockListFile<FD, FS, D, T>,
+    txn_id: TxnId,
+    passes: u32,
+) -> TCResult<BlockListFile<FD, FS, D, T>>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+    BlockListFile<FD, FS, D, T>: Clone,
+{
+    let mut source = source;
+
+    for pass in 0..passes {
+        let shift = pass * RADIX_BITS;
+
+        // One offset buffer per bucket, flushed to disk the moment it fills
+        // -- this pass never holds more than `RADIX * PER_BLOCK` offsets in
+        // memory at once, unlike materializing all of the pass's offsets into a
+        // single `Vec` up front.
+        let mut buffers: Vec<Vec<u64>> = (0..RADIX).map(|_| Vec::with_capacity(PER_BLOCK)).collect();
+        let mut bucket_blocks: Vec<Vec<BlockListFile<FD, FS, D, T>>> =
+            (0..RADIX).map(|_| Vec::new()).collect();
+
+        let mut blocks = source
+            .clone()
+            .into_stream(txn_id)
+            .map_ok(|array| array.type_cast());
+
+        while let Some(block) = blocks.try_next().await? {
+            let block: ArrayExt<u64> = block;
+            for offset in block.to_vec() {
+                let digit = digit_at(offset, shift);
+                buffers[digit].push(offset);
+
+                if buffers[digit].len() == PER_BLOCK {
+                    let full = std::mem::replace(&mut buffers[digit], Vec::with_capacity(PER_BLOCK));
+                    bucket_blocks[digit].push(spill_block(txn, txn_id, full).await?);
+                }
+            }
+        }
+
+        for (digit, buffer) in buffers.into_iter().enumerate() {
+            if !buffer.is_empty() {
+                bucket_blocks[digit].push(spill_block(txn, txn_id, buffer).await?);
+            }
+        }
+
+        let file: FD = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        // stream each bucket's already-spilled blocks back out in bucket
+        // (i.e. digit) order to assemble this pass's sorted output, rather
+        // than re-reading a single in-memory `Vec`
+        let blocks = stream::iter(bucket_blocks.into_iter().flatten())
+            .then(move |bucket_file| async move { TCResult::Ok(bucket_file.into_stream(txn_id)) })
+            .try_flatten();
+
+        source =
+            BlockListFile::from_blocks(file, txn_id, None, UIntType::U64.into(), Box::pin(blocks))
+                .await?;
+    }
+
+    Ok(source)
+}
+
+/// Spill `buffer` (at most `PER_BLOCK` offsets) to its own single-block
+/// temporary `BlockListFile`, so a bucket's accumulated offsets reach disk
+/// as soon as one block's worth is ready instead of waiting for the whole
+/// radix pass to finish before anything is written out.
+async fn spill_block<FD, FS, D, T>(
+    txn: &T,
+    txn_id: TxnId,
+    buffer: Vec<u64>,
+) -> TCResult<BlockListFile<FD, FS, D, T>>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    let file: FD = txn
+        .context()
+        .create_file_tmp(txn_id, TensorType::Dense)
+        .await?;
+
+    let block = stream::iter(vec![TCResult::Ok(ArrayExt::from(buffer).into())]);
+    BlockListFile::from_blocks(file, txn_id, None, UIntType::U64.into(), Box::pin(block)).await
 }
 
 fn coords_to_offsets<S: Stream<Item = TCResult<Coords>> + Unpin>(
@@ -104,3 +242,39 @@ fn offsets_to_coords<'a, S: Stream<Item = TCResult<Offsets>> + Unpin + 'a>(
 ) -> impl Stream<Item = TCResult<Coords>> + Unpin + 'a {
     offsets.map_ok(move |block| Coords::from_offsets(block, &shape))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every pass's digit must fit in `RADIX`, and reassembling a full
+    /// offset back from its per-pass digits (shifting each one back into
+    /// place) must recover the original value -- if it didn't, radix_sort's
+    /// bucket assignment would silently scatter an offset to the wrong
+    /// place with no panic to catch it.
+    #[test]
+    fn digit_at_round_trips_through_all_passes() {
+        let max_offset: u64 = 5_000_000_000;
+        let passes = (bits_needed(max_offset) + RADIX_BITS - 1) / RADIX_BITS;
+
+        for offset in [0, 1, RADIX as u64 - 1, RADIX as u64, max_offset] {
+            let mut rebuilt: u64 = 0;
+            for pass in 0..passes {
+                let shift = pass * RADIX_BITS;
+                let digit = digit_at(offset, shift);
+                assert!(digit < RADIX);
+                rebuilt |= (digit as u64) << shift;
+            }
+
+            assert_eq!(rebuilt, offset);
+        }
+    }
+
+    #[test]
+    fn bits_needed_matches_pass_count() {
+        assert_eq!(bits_needed(0), 0);
+        assert_eq!(bits_needed(1), 1);
+        assert_eq!(bits_needed(RADIX as u64 - 1), RADIX_BITS);
+        assert_eq!(bits_needed(RADIX as u64), RADIX_BITS + 1);
+    }
+}
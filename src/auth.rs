@@ -0,0 +1,193 @@
+//! Capability tokens with macaroon-style attenuation: a root [`Token`] is
+//! minted by whatever holds the corresponding root secret (see
+//! [`Authority`]), and can then be narrowed by any holder -- the client,
+//! or anything it delegates to -- into a strictly weaker token by
+//! appending [`Caveat`]s, without ever contacting the issuer again or
+//! learning the root secret itself.
+//!
+//! This works by chaining HMACs: a fresh token's signature is
+//! `HMAC(root_secret, identifier)`, and appending a caveat `c` updates the
+//! signature to `HMAC(old_signature, c)`. Appending is therefore free (it
+//! only needs the current signature, which travels with the token), but
+//! nobody can produce a signature for a caveat list other than the one
+//! they actually appended without knowing the root secret at some point in
+//! the chain. [`Authority::verify`] is the only place that secret is ever
+//! needed again: it recomputes the same chain from scratch and checks it
+//! against the one the token carries, then checks every caveat's
+//! predicate against the request actually being made.
+//!
+//! [`Caveat::Path`] is written against `crate::value::link::{PathSegment,
+//! TCPath}`'s `Display` impl, the same way `crate::state::Authorized`
+//! references those types.
+
+use std::fmt;
+
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+use crate::error;
+use crate::transaction::TxnId;
+use crate::value::{TCResult, ValueId};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The kind of request a [`Token`] is being checked against, i.e. the
+/// inputs every [`Caveat::matches`] predicate gets to see. `path` is the
+/// absolute path of the resource being accessed, when the caller has one
+/// to offer -- `Collection::get`/`put` don't thread a path through today,
+/// so a [`Caveat::Path`] can only be enforced where a path is actually
+/// available (currently `State::post`, via its `method: &PathSegment`).
+pub struct Request<'a> {
+    pub path: Option<&'a str>,
+    pub op: &'static str,
+    pub txn_id: &'a TxnId,
+}
+
+/// A single attenuation appended to a [`Token`]: a predicate the request
+/// context must satisfy for the capability to still apply. Unrecognized
+/// caveat bytes (e.g. appended by a newer version of this code) are never
+/// silently ignored by [`Authority::verify`] -- only caveats that parse
+/// into one of these variants and whose predicate matches are accepted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Caveat {
+    /// only authorize requests whose path starts with this prefix
+    Path(String),
+    /// only authorize requests of this kind ("get", "put", or "post")
+    Op(String),
+    /// only authorize requests with a `TxnId` clock older than this,
+    /// in nanoseconds since the Unix epoch (matches `TxnId::new`'s clock)
+    Expires(u128),
+}
+
+impl Caveat {
+    /// The exact byte string chained into the token's signature for this
+    /// caveat. A `Token` never needs to parse a caveat back out of the
+    /// signature itself, so this is the only wire format it requires.
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Caveat::Path(prefix) => format!("path<{}", prefix).into_bytes(),
+            Caveat::Op(op) => format!("op<{}", op).into_bytes(),
+            Caveat::Expires(not_after) => format!("expires<{}", not_after).into_bytes(),
+        }
+    }
+
+    fn matches(&self, request: &Request) -> bool {
+        match self {
+            Caveat::Path(prefix) => request
+                .path
+                .map(|path| path.starts_with(prefix.as_str()))
+                .unwrap_or(false),
+            Caveat::Op(op) => op == request.op,
+            Caveat::Expires(not_after) => request.txn_id.time() < *not_after,
+        }
+    }
+}
+
+impl fmt::Display for Caveat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Caveat::Path(prefix) => write!(f, "path starts with {}", prefix),
+            Caveat::Op(op) => write!(f, "op is {}", op),
+            Caveat::Expires(not_after) => write!(f, "expires at {}", not_after),
+        }
+    }
+}
+
+/// A capability to perform some set of requests, attenuated down from a
+/// root [`Token`] by appending zero or more [`Caveat`]s. Cloning a `Token`
+/// and handing the clone to a less-trusted caller is always safe: nothing
+/// reachable from a `Token` exposes the root secret it was minted with.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Token {
+    root_id: ValueId,
+    identifier: Vec<u8>,
+    caveats: Vec<Caveat>,
+    signature: Vec<u8>,
+}
+
+impl Token {
+    /// Narrow this token by appending `caveat`, updating its signature to
+    /// `HMAC(self.signature, caveat)`. This needs no knowledge of the root
+    /// secret, so it's safe to call from an untrusted client.
+    pub fn attenuate(&self, caveat: Caveat) -> Token {
+        let mut mac = HmacSha256::new_varkey(&self.signature).expect("HMAC accepts any key length");
+        mac.update(&caveat.to_bytes());
+
+        let mut caveats = self.caveats.clone();
+        caveats.push(caveat);
+
+        Token {
+            root_id: self.root_id.clone(),
+            identifier: self.identifier.clone(),
+            caveats,
+            signature: mac.finalize().into_bytes().to_vec(),
+        }
+    }
+
+    pub fn root_id(&self) -> &ValueId {
+        &self.root_id
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "token issued by {}", self.root_id)
+    }
+}
+
+/// A source of root secrets, identified by the `ValueId` a [`Token`]
+/// stores as its `root_id`. Whatever mints and verifies tokens in a given
+/// deployment (e.g. the host serving a request) should implement this;
+/// nothing in this module assumes a particular secret store.
+pub trait Authority: Send + Sync {
+    fn root_secret(&self, root_id: &ValueId) -> TCResult<Vec<u8>>;
+
+    /// Mint a fresh, un-attenuated token rooted at `root_id`, using the
+    /// secret looked up via [`Authority::root_secret`].
+    fn issue(&self, root_id: ValueId, identifier: Vec<u8>) -> TCResult<Token> {
+        let root_secret = self.root_secret(&root_id)?;
+
+        let mut mac = HmacSha256::new_varkey(&root_secret)
+            .map_err(|e| error::internal(format!("invalid root secret: {}", e)))?;
+        mac.update(&identifier);
+
+        Ok(Token {
+            root_id,
+            identifier,
+            caveats: Vec::new(),
+            signature: mac.finalize().into_bytes().to_vec(),
+        })
+    }
+
+    /// Recompute `token`'s signature chain from the root secret on record
+    /// for `token.root_id`, and reject the token if that doesn't match the
+    /// signature it actually carries (i.e. it was forged, or attenuated by
+    /// someone without the root secret tampering with a caveat in transit)
+    /// or if any caveat it carries fails to match `request`.
+    fn verify(&self, token: &Token, request: &Request) -> TCResult<()> {
+        let root_secret = self.root_secret(&token.root_id)?;
+
+        let mut mac = HmacSha256::new_varkey(&root_secret)
+            .map_err(|e| error::internal(format!("invalid root secret: {}", e)))?;
+        mac.update(&token.identifier);
+        let mut signature = mac.finalize().into_bytes().to_vec();
+
+        for caveat in &token.caveats {
+            let mut mac = HmacSha256::new_varkey(&signature).expect("HMAC accepts any key length");
+            mac.update(&caveat.to_bytes());
+            signature = mac.finalize().into_bytes().to_vec();
+        }
+
+        if signature != token.signature {
+            return Err(error::unauthorized("invalid token signature"));
+        }
+
+        for caveat in &token.caveats {
+            if !caveat.matches(request) {
+                return Err(error::forbidden("token caveat not satisfied", caveat));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -4,21 +4,38 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::stream::{self, Stream, StreamExt};
-use futures::TryFutureExt;
+use futures::{future, TryFutureExt};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
 
 use crate::auth::Auth;
 use crate::class::*;
 use crate::collection::class::*;
 use crate::collection::{Collection, CollectionBase, CollectionBaseType};
 use crate::error;
-use crate::transaction::lock::{Mutable, TxnLock};
+use crate::transaction::lock::{Mutable, RwLock, TxnLock};
 use crate::transaction::{Transact, Txn, TxnId};
 use crate::value::class::ValueClass;
 use crate::value::op::OpDef;
 use crate::value::{Link, TCPath, Value, ValueId, ValueType};
 
+use super::queue::{JobId, JobQueue, JobState};
 use super::{Chain, ChainInstance, ChainType};
 
+/// The number of unconsumed changes a [`NullChain::subscribe`] caller may lag
+/// behind before it's forced to resync by re-reading `/object` directly,
+/// mirroring `collection::blob::Blob`'s own `SUBSCRIBE_BUFFER`.
+const SUBSCRIBE_BUFFER: usize = 128;
+
+/// A single change to the value a [`NullChain`] wraps at `/object`, emitted
+/// in commit order: the value a committed write replaced, followed by the
+/// value it was replaced with.
+#[derive(Clone)]
+pub enum Change {
+    Assert(Value),
+    Retract(Value),
+}
+
 const ERR_COLLECTION_VIEW: &str = "Chain does not support CollectionView; \
 consider making a copy of the Collection first";
 
@@ -61,6 +78,14 @@ impl From<Value> for ChainState {
 pub struct NullChain {
     state: ChainState,
     ops: TxnLock<Mutable<HashMap<ValueId, OpDef>>>,
+    // the value a write to `/object` replaced, staged under the writing
+    // txn until commit, when it's paired with the now-committed value and
+    // fanned out through `changes` as a `Retract`/`Assert` pair
+    pending: RwLock<HashMap<TxnId, Value>>,
+    changes: broadcast::Sender<Change>,
+    // deferred `OpDef::Deferred` jobs enqueued under `/queue`, run by whatever
+    // worker next calls `run_worker` rather than inline in the enqueuing txn
+    jobs: JobQueue,
 }
 
 impl NullChain {
@@ -70,6 +95,7 @@ impl NullChain {
         schema: Value,
         ops: HashMap<ValueId, OpDef>,
     ) -> TCResult<NullChain> {
+        let repo = txn.repo();
         let dtype = TCType::from_path(&dtype)?;
         let state = match dtype {
             TCType::Collection(ct) => match ct {
@@ -93,8 +119,59 @@ impl NullChain {
         Ok(NullChain {
             state,
             ops: TxnLock::new("NullChain ops", ops.into()),
+            pending: RwLock::new(HashMap::new()),
+            changes: broadcast::channel(SUBSCRIBE_BUFFER).0,
+            jobs: JobQueue::new(repo),
         })
     }
+
+    /// The status (and, once `Done`, the fact) of a job previously enqueued
+    /// by a `post` to `/queue/<name>`, looked up by the [`JobId`] that
+    /// `post` returned.
+    pub async fn job_status(&self, id: JobId) -> TCResult<JobState> {
+        self.jobs.status(id).await
+    }
+
+    /// Claim and run one enqueued job, if any is eligible, on behalf of
+    /// `worker`. A deployment would call this in a loop from a dedicated
+    /// worker task; see [`queue::JobQueue::claim_and_run`] for why nothing
+    /// here spawns that task itself.
+    pub async fn run_worker(&self, txn: Arc<Txn>, worker: ValueId) -> Option<JobId> {
+        self.jobs.claim_and_run(txn, worker, None).await
+    }
+
+    /// Observe live changes to the value this chain wraps at `/object`, as
+    /// `assert`/`retract` events ordered by commit, so a subscriber never has
+    /// to re-run `get` to notice a write. Only meaningful for a chain wrapping
+    /// a `Value`: a chain wrapping a `CollectionBase` has no single value of
+    /// its own to assert/retract, since what changes is the wrapped
+    /// collection's own rows -- follow `CollectionInstance::subscribe` on
+    /// that collection instead (see `collection::blob::Blob::subscribe` for
+    /// the established pattern this mirrors), the same way `host/collection`'s
+    /// `TableFile::on_update`/`on_finalize` hooks already give a `Table` its
+    /// own per-row equivalent of this signal.
+    pub async fn subscribe(&self, txn: Arc<Txn>) -> TCResult<TCStream<Change>> {
+        match &self.state {
+            ChainState::Value(value) => {
+                let current = value.read(txn.id()).await?.clone();
+                let snapshot = stream::once(future::ready(Change::Assert(current)));
+
+                let live = BroadcastStream::new(self.changes.subscribe()).filter_map(|change| {
+                    future::ready(match change {
+                        Ok(change) => Some(change),
+                        // a lagging subscriber missed changes; it should re-read
+                        // `/object` to resync rather than trust an incomplete stream
+                        Err(_lagged) => None,
+                    })
+                });
+
+                Ok(Box::pin(snapshot.chain(live)))
+            }
+            ChainState::Collection(_) => Err(error::not_implemented(
+                "subscribing to a Chain wrapping a Collection (use the Collection's own subscribe)",
+            )),
+        }
+    }
 }
 
 impl Instance for NullChain {
@@ -126,6 +203,20 @@ impl ChainInstance for NullChain {
                         .await
                 }
             }
+        } else if path.len() == 2 && path[0].as_str() == "queue" {
+            let id: JobId = path[1]
+                .as_str()
+                .parse()
+                .map_err(|_| error::bad_request("Not a job id", &path[1]))?;
+
+            let status = match self.job_status(id).await? {
+                JobState::New => "new".to_string(),
+                JobState::Claimed { worker, .. } => format!("claimed by {}", worker),
+                JobState::Done => "done".to_string(),
+                JobState::Failed(cause) => format!("failed: {}", cause),
+            };
+
+            Ok(State::Value(status.into()))
         } else if path.len() == 1 {
             if let Some(op) = self.ops.read(txn.id()).await?.get(&path[0]) {
                 if let OpDef::Get((key_name, def)) = op {
@@ -162,7 +253,10 @@ impl ChainInstance for NullChain {
                             value.class().into(),
                             format!("Chain wraps {}", value.class()),
                         )?;
+
+                        let old = value.clone();
                         *value = new_value.try_into()?;
+                        self.pending.write().await.insert(txn.id().clone(), old);
                         Ok(())
                     } else {
                         Err(error::bad_request("Value has no such attribute", key))
@@ -190,19 +284,31 @@ impl ChainInstance for NullChain {
         if path.is_empty() {
             Err(error::method_not_allowed("NullChain::post"))
         } else if path.len() == 1 {
-            if let Some(OpDef::Post(def)) = self.ops.read(txn.id()).await?.get(&path[0]) {
-                println!(
-                    "Chain::post {} def: {}",
-                    path,
-                    def.iter()
-                        .map(|(name, op)| format!("{}: {}", name, op))
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                );
-                let data = data.chain(stream::iter(def.to_vec()));
-                txn.execute_and_stream(data, auth).await
-            } else {
-                Err(error::not_found(path))
+            match self.ops.read(txn.id()).await?.get(&path[0]).cloned() {
+                Some(OpDef::Deferred(def)) => {
+                    // defer execution to whatever worker next calls
+                    // `run_worker`, instead of running inline in this (the
+                    // enqueuing) txn
+                    let mut params: Vec<(ValueId, Value)> = data.collect().await;
+                    params.extend(def.to_vec());
+                    let id = self.jobs.enqueue(OpDef::Post(params.into()), auth).await;
+
+                    let id: Value = id.to_string().into();
+                    Ok(Box::pin(stream::once(future::ready(id))))
+                }
+                Some(OpDef::Post(def)) => {
+                    println!(
+                        "Chain::post {} def: {}",
+                        path,
+                        def.iter()
+                            .map(|(name, op)| format!("{}: {}", name, op))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    );
+                    let data = data.chain(stream::iter(def.to_vec()));
+                    txn.execute_and_stream(data, auth).await
+                }
+                _ => Err(error::not_found(path)),
             }
         } else {
             Err(error::not_found(path))
@@ -219,10 +325,20 @@ impl Transact for NullChain {
     async fn commit(&self, txn_id: &TxnId) {
         self.state.commit(txn_id).await;
         self.ops.commit(txn_id).await;
+
+        if let Some(old) = self.pending.write().await.remove(txn_id) {
+            if let ChainState::Value(value) = &self.state {
+                if let Ok(new) = value.read(txn_id).await {
+                    let _ = self.changes.send(Change::Retract(old));
+                    let _ = self.changes.send(Change::Assert(new.clone()));
+                }
+            }
+        }
     }
 
     async fn rollback(&self, txn_id: &TxnId) {
         self.state.rollback(txn_id).await;
         self.ops.rollback(txn_id).await;
+        self.pending.write().await.remove(txn_id);
     }
 }
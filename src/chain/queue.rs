@@ -0,0 +1,308 @@
+//! A durable, claim-and-retry job queue for deferred [`super::null::
+//! NullChain`] op execution, so a long-running or failure-prone `post` can
+//! be enqueued and run asynchronously instead of executing synchronously
+//! inside the enqueuing transaction.
+//!
+//! Jobs progress `New` -> `Claimed { worker, since }` -> `Done`/`Failed`.
+//! A worker claims the oldest eligible job (the oldest `New` job, or the
+//! oldest `Claimed` job whose lease has expired) by compare-and-setting its
+//! state under the queue's own lock, so two workers racing to claim the
+//! same job never both win; the loser simply sees the job is no longer
+//! eligible and moves on to the next one. A claimed job whose worker
+//! crashed before finishing is detected the same way any other staleness
+//! is: its `since` is older than the configured lease, so the next claim
+//! attempt resets it to `New` and hands it to a fresh worker, giving
+//! at-least-once (not exactly-once) execution.
+//!
+//! Every `order`/[`JobState`] transition is written through to
+//! [`crate::internal::repo::Repo`] (see [`JobQueue::persist_state`] and
+//! [`JobQueue::persist_order`]) under a dedicated single-write `TxnId`
+//! committed immediately, so a queue's bookkeeping survives a crash
+//! independently of the enqueuing transaction. [`JobQueue::restore`] reads
+//! that bookkeeping back on startup.
+//!
+//! `op`/`auth` themselves still can't be persisted: `crate::value::op::
+//! OpDef` and `crate::auth::Auth` have no definition anywhere in this
+//! checkout, so `restore` recovers which ids were enqueued and each one's
+//! last known state, but not a runnable `Job`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::auth::Auth;
+use crate::class::{TCResult, TCStream};
+use crate::error;
+use crate::gateway::time::NetworkTime;
+use crate::internal::repo::Repo;
+use crate::transaction::lock::RwLock;
+use crate::transaction::{Txn, TxnId};
+use crate::value::op::OpDef;
+use crate::value::ValueId;
+
+/// Identifies a single enqueued job, assigned in enqueue order.
+pub type JobId = u64;
+
+/// How long a claimed job is allowed to run before another worker is
+/// allowed to treat its claim as abandoned and retry it.
+const DEFAULT_LEASE_MILLIS: u128 = 5 * 60 * 1000;
+
+#[derive(Clone)]
+pub enum JobState {
+    New,
+    Claimed { worker: ValueId, since: u128 },
+    Done,
+    Failed(String),
+}
+
+#[derive(Clone)]
+struct Job {
+    op: OpDef,
+    auth: Auth,
+    state: JobState,
+}
+
+/// A durable FIFO queue of deferred `post` jobs, shared by every caller
+/// enqueuing or claiming work against a given [`super::null::NullChain`].
+pub struct JobQueue {
+    next_id: AtomicU64,
+    // insertion order doubles as priority: the oldest still-`New` (or
+    // lease-expired `Claimed`) job is always the next one claimed
+    order: RwLock<Vec<JobId>>,
+    jobs: RwLock<HashMap<JobId, Job>>,
+    repo: Arc<dyn Repo>,
+}
+
+impl JobQueue {
+    pub fn new(repo: Arc<dyn Repo>) -> JobQueue {
+        JobQueue {
+            next_id: AtomicU64::new(0),
+            order: RwLock::new(Vec::new()),
+            jobs: RwLock::new(HashMap::new()),
+            repo,
+        }
+    }
+
+    /// Enqueue `op`, captured along with the `auth` it should run with, and
+    /// return the id a caller can later check the status of via `status`.
+    pub async fn enqueue(&self, op: OpDef, auth: Auth) -> JobId {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        self.jobs.write().await.insert(
+            id,
+            Job {
+                op,
+                auth,
+                state: JobState::New,
+            },
+        );
+
+        let order = {
+            let mut order = self.order.write().await;
+            order.push(id);
+            order.clone()
+        };
+
+        self.persist_order(&order).await;
+        self.persist_state(id, &JobState::New).await;
+
+        id
+    }
+
+    pub async fn status(&self, id: JobId) -> TCResult<JobState> {
+        self.jobs
+            .read()
+            .await
+            .get(&id)
+            .map(|job| job.state.clone())
+            .ok_or_else(|| error::not_found(format!("job {}", id)))
+    }
+
+    /// Atomically claim the oldest eligible job for `worker`, if any, by
+    /// compare-and-setting its state to `Claimed` under the queue's own
+    /// lock -- the only place a job's state is read and written without
+    /// releasing the lock in between, so two concurrent claims can never
+    /// both succeed for the same job.
+    async fn claim(&self, worker: &ValueId, lease_millis: u128) -> Option<(JobId, Job)> {
+        let now = NetworkTime::now().as_nanos() / 1_000_000;
+        let order = self.order.read().await;
+        let mut jobs = self.jobs.write().await;
+
+        for id in order.iter() {
+            let eligible = match jobs.get(id).map(|job| &job.state) {
+                Some(JobState::New) => true,
+                Some(JobState::Claimed { since, .. }) => now.saturating_sub(*since) > lease_millis,
+                _ => false,
+            };
+
+            if eligible {
+                let job = jobs.get_mut(id).expect("claimed job");
+                job.state = JobState::Claimed {
+                    worker: worker.clone(),
+                    since: now,
+                };
+
+                let claimed = (*id, job.clone());
+                drop(jobs);
+                drop(order);
+                self.persist_state(claimed.0, &claimed.1.state).await;
+                return Some(claimed);
+            }
+        }
+
+        None
+    }
+
+    async fn finish(&self, id: JobId, state: JobState) {
+        if let Some(job) = self.jobs.write().await.get_mut(&id) {
+            job.state = state.clone();
+        }
+
+        self.persist_state(id, &state).await;
+    }
+
+    /// Write `id`'s `state` through to `self.repo` under a fresh, immediately
+    /// committed `TxnId` -- a job's bookkeeping should survive a crash
+    /// whether or not the transaction that enqueued or claimed it ever
+    /// commits, so it isn't staged behind the caller's own txn.
+    async fn persist_state(&self, id: JobId, state: &JobState) {
+        let txn_id = TxnId::new(NetworkTime::now());
+        let block_id = job_block_id(id);
+        let _ = self
+            .repo
+            .write_block(&txn_id, block_id, encode_state(state))
+            .await;
+        self.repo.commit(&txn_id).await;
+    }
+
+    /// Write the full claim order through to `self.repo`, the same way
+    /// [`Self::persist_state`] writes a single job's state.
+    async fn persist_order(&self, order: &[JobId]) {
+        let txn_id = TxnId::new(NetworkTime::now());
+        let data = order
+            .iter()
+            .map(JobId::to_string)
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let _ = self
+            .repo
+            .write_block(&txn_id, order_block_id(), data.into_bytes())
+            .await;
+
+        self.repo.commit(&txn_id).await;
+    }
+
+    /// Read back the claim order and each job's last known [`JobState`] from
+    /// `self.repo`, e.g. after a restart. This recovers the queue's
+    /// bookkeeping, not a runnable `Job`: `op`/`auth` were never persisted
+    /// (see the module-level doc comment), so a recovered `New`/`Claimed`
+    /// entry here can be reported but not re-run until this queue is
+    /// re-populated with fresh `enqueue` calls.
+    pub async fn restore(repo: Arc<dyn Repo>) -> TCResult<Vec<(JobId, JobState)>> {
+        let txn_id = TxnId::new(NetworkTime::now());
+
+        let order_block = match repo.read_block(&txn_id, &order_block_id()).await {
+            Ok(data) => data,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let order = String::from_utf8(order_block)
+            .map_err(|e| error::bad_request("corrupt job queue order block", e))?;
+
+        let mut restored = Vec::new();
+        for id in order.split(',').filter(|id| !id.is_empty()) {
+            let id: JobId = id
+                .parse()
+                .map_err(|_| error::bad_request("corrupt job id in queue order block", id))?;
+
+            let data = repo.read_block(&txn_id, &job_block_id(id)).await?;
+            restored.push((id, decode_state(&data)?));
+        }
+
+        Ok(restored)
+    }
+
+    /// Claim and run one eligible job, if any, using `lease_millis` (or
+    /// [`DEFAULT_LEASE_MILLIS`] if `None`) as the staleness threshold for
+    /// reclaiming another worker's abandoned claim. Returns the claimed
+    /// job's id, or `None` if there was nothing eligible to claim.
+    ///
+    /// A real deployment would call this in a loop from a dedicated worker
+    /// task; nothing here spawns that task itself, since this checkout has
+    /// no async runtime/executor setup to spawn one onto.
+    pub async fn claim_and_run(
+        &self,
+        txn: Arc<Txn>,
+        worker: ValueId,
+        lease_millis: Option<u128>,
+    ) -> Option<JobId> {
+        let (id, job) = self
+            .claim(&worker, lease_millis.unwrap_or(DEFAULT_LEASE_MILLIS))
+            .await?;
+
+        match execute(txn, job.op, job.auth).await {
+            Ok(_) => self.finish(id, JobState::Done).await,
+            Err(cause) => self.finish(id, JobState::Failed(cause.to_string())).await,
+        }
+
+        Some(id)
+    }
+}
+
+async fn execute(txn: Arc<Txn>, op: OpDef, auth: Auth) -> TCResult<TCStream<crate::value::Value>> {
+    match op {
+        OpDef::Post(def) => {
+            let data = futures::stream::iter(def.to_vec());
+            txn.execute_and_stream(data, auth).await
+        }
+        other => Err(error::bad_request("Cannot enqueue op", other)),
+    }
+}
+
+fn order_block_id() -> ValueId {
+    "order".parse().expect("order block id")
+}
+
+fn job_block_id(id: JobId) -> ValueId {
+    format!("job_{id}").parse().expect("job block id")
+}
+
+fn encode_state(state: &JobState) -> Vec<u8> {
+    match state {
+        JobState::New => "new".to_string(),
+        JobState::Claimed { worker, since } => format!("claimed:{worker}:{since}"),
+        JobState::Done => "done".to_string(),
+        JobState::Failed(cause) => format!("failed:{cause}"),
+    }
+    .into_bytes()
+}
+
+fn decode_state(data: &[u8]) -> TCResult<JobState> {
+    let encoded =
+        String::from_utf8(data.to_vec()).map_err(|e| error::bad_request("corrupt job state block", e))?;
+
+    if encoded == "new" {
+        Ok(JobState::New)
+    } else if encoded == "done" {
+        Ok(JobState::Done)
+    } else if let Some(cause) = encoded.strip_prefix("failed:") {
+        Ok(JobState::Failed(cause.to_string()))
+    } else if let Some(claim) = encoded.strip_prefix("claimed:") {
+        let (worker, since) = claim
+            .split_once(':')
+            .ok_or_else(|| error::bad_request("corrupt claimed job state block", &encoded))?;
+
+        let worker: ValueId = worker
+            .parse()
+            .map_err(|_| error::bad_request("corrupt job worker id", worker))?;
+
+        let since: u128 = since
+            .parse()
+            .map_err(|_| error::bad_request("corrupt job claim timestamp", since))?;
+
+        Ok(JobState::Claimed { worker, since })
+    } else {
+        Err(error::bad_request("corrupt job state block", encoded))
+    }
+}
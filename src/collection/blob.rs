@@ -0,0 +1,494 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::class::{Class, Instance, NativeClass, TCResult, TCStream};
+use crate::collection::class::*;
+use crate::collection::{Collection, CollectionBase, CollectionItem};
+use crate::error;
+use crate::scalar::{label, Link, Scalar, TCPath, Value};
+use crate::transaction::lock::RwLock;
+use crate::transaction::{Transact, Txn, TxnId};
+
+use super::null::{PutMode, PutResult, UpdateVersion};
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct BlobType;
+
+impl Class for BlobType {
+    type Instance = Blob;
+}
+
+impl NativeClass for BlobType {
+    fn from_path(path: &TCPath) -> TCResult<Self> {
+        let suffix = path.from_path(&Self::prefix())?;
+
+        if suffix.is_empty() {
+            Ok(BlobType)
+        } else {
+            Err(error::not_found(path))
+        }
+    }
+
+    fn prefix() -> TCPath {
+        CollectionType::prefix().join(label("blob").into())
+    }
+}
+
+impl From<BlobType> for CollectionType {
+    fn from(_: BlobType) -> CollectionType {
+        CollectionType::Base(CollectionBaseType::Blob)
+    }
+}
+
+impl From<BlobType> for Link {
+    fn from(_: BlobType) -> Link {
+        BlobType::prefix().into()
+    }
+}
+
+impl fmt::Display for BlobType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "type: Blob Collection")
+    }
+}
+
+/// The byte range selected by a GET/read against a [`Blob`].
+#[derive(Clone, Debug)]
+pub enum GetRange {
+    Bounded(Range<u64>),
+    Offset(u64),
+    Suffix(u64),
+}
+
+impl GetRange {
+    fn resolve(&self, len: u64) -> Range<u64> {
+        match self {
+            GetRange::Bounded(range) => range.start.min(len)..range.end.min(len),
+            GetRange::Offset(start) => (*start).min(len)..len,
+            GetRange::Suffix(n) => len.saturating_sub(*n)..len,
+        }
+    }
+}
+
+impl TryFrom<Value> for GetRange {
+    type Error = error::TCError;
+
+    fn try_from(value: Value) -> TCResult<GetRange> {
+        let (start, len): (u64, u64) = value.try_into()?;
+        Ok(GetRange::Bounded(start..(start + len)))
+    }
+}
+
+/// Metadata about a stored object, mirroring an object-store's `HEAD` response.
+#[derive(Clone, Debug)]
+pub struct ObjectMeta {
+    pub size: u64,
+    pub last_modified: u128,
+    pub e_tag: String,
+}
+
+/// A pluggable backend for [`Blob`] storage, object-safe so a filesystem or
+/// remote implementation can be substituted for the default in-memory store.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn get_opts(&self, path: &TCPath, range: GetRange) -> TCResult<(Bytes, ObjectMeta)>;
+
+    async fn list(&self, prefix: &TCPath) -> TCResult<Vec<(TCPath, ObjectMeta)>>;
+
+    async fn put(&self, path: &TCPath, data: Bytes) -> TCResult<ObjectMeta>;
+
+    async fn delete(&self, path: &TCPath) -> TCResult<()>;
+}
+
+/// The default, dependency-free [`ObjectStore`] backend.
+#[derive(Default)]
+pub struct MemoryStore {
+    objects: RwLock<BTreeMap<TCPath, (Bytes, ObjectMeta)>>,
+}
+
+#[async_trait]
+impl ObjectStore for MemoryStore {
+    async fn get_opts(&self, path: &TCPath, range: GetRange) -> TCResult<(Bytes, ObjectMeta)> {
+        let objects = self.objects.read().await;
+        let (data, meta) = objects
+            .get(path)
+            .ok_or_else(|| error::not_found(path))?
+            .clone();
+
+        let selected = range.resolve(data.len() as u64);
+        let slice = data.slice((selected.start as usize)..(selected.end as usize));
+        Ok((slice, meta))
+    }
+
+    async fn list(&self, prefix: &TCPath) -> TCResult<Vec<(TCPath, ObjectMeta)>> {
+        let objects = self.objects.read().await;
+        Ok(objects
+            .iter()
+            .filter(|(path, _)| path.starts_with(prefix))
+            .map(|(path, (_, meta))| (path.clone(), meta.clone()))
+            .collect())
+    }
+
+    async fn put(&self, path: &TCPath, data: Bytes) -> TCResult<ObjectMeta> {
+        let meta = ObjectMeta {
+            size: data.len() as u64,
+            last_modified: crate::gateway::time::NetworkTime::now().as_nanos(),
+            e_tag: {
+                let mut hasher = DefaultHasher::new();
+                data.hash(&mut hasher);
+                format!("{:x}", hasher.finish())
+            },
+        };
+
+        self.objects
+            .write()
+            .await
+            .insert(path.clone(), (data, meta.clone()));
+
+        Ok(meta)
+    }
+
+    async fn delete(&self, path: &TCPath) -> TCResult<()> {
+        self.objects.write().await.remove(path);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Overlay {
+    puts: BTreeMap<TCPath, Bytes>,
+    deletes: Vec<TCPath>,
+}
+
+/// Identifies an in-progress multipart upload, scoped to a single `TxnId`.
+pub type MultipartId = u64;
+
+/// Identifies a single part within a multipart upload.
+pub type PartId = usize;
+
+/// The minimum size (in bytes) of any part other than the last one in an upload.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Default)]
+struct MultipartUpload {
+    selector: Value,
+    parts: BTreeMap<PartId, Bytes>,
+}
+
+/// The number of unconsumed deltas a subscriber may lag behind before it is
+/// forced to resync from a fresh snapshot.
+const SUBSCRIBE_BUFFER: usize = 128;
+
+#[derive(Clone)]
+pub struct Blob {
+    path: TCPath,
+    store: Arc<dyn ObjectStore>,
+    pending: RwLock<BTreeMap<TxnId, Overlay>>,
+    version: RwLock<u64>,
+    changes: broadcast::Sender<Bytes>,
+    multipart: RwLock<HashMap<TxnId, HashMap<MultipartId, MultipartUpload>>>,
+    next_multipart_id: Arc<AtomicU64>,
+}
+
+impl Blob {
+    pub fn create(path: TCPath) -> Blob {
+        Blob {
+            path,
+            store: Arc::new(MemoryStore::default()),
+            pending: RwLock::new(BTreeMap::new()),
+            version: RwLock::new(0),
+            changes: broadcast::channel(SUBSCRIBE_BUFFER).0,
+            multipart: RwLock::new(HashMap::new()),
+            next_multipart_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn with_store(path: TCPath, store: Arc<dyn ObjectStore>) -> Blob {
+        Blob {
+            path,
+            store,
+            pending: RwLock::new(BTreeMap::new()),
+            version: RwLock::new(0),
+            changes: broadcast::channel(SUBSCRIBE_BUFFER).0,
+            multipart: RwLock::new(HashMap::new()),
+            next_multipart_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Begin a multipart upload for `selector`, staged under this transaction.
+    pub async fn create_multipart(&self, txn: &Txn, selector: Value) -> TCResult<MultipartId> {
+        let id = self.next_multipart_id.fetch_add(1, Ordering::SeqCst);
+
+        let mut multipart = self.multipart.write().await;
+        multipart
+            .entry(txn.id().clone())
+            .or_default()
+            .insert(id, MultipartUpload { selector, parts: BTreeMap::new() });
+
+        Ok(id)
+    }
+
+    /// Stage a single part of an in-progress multipart upload.
+    pub async fn put_part(
+        &self,
+        txn: &Txn,
+        id: MultipartId,
+        part_idx: PartId,
+        data: Bytes,
+    ) -> TCResult<PartId> {
+        let mut multipart = self.multipart.write().await;
+        let upload = multipart
+            .get_mut(txn.id())
+            .and_then(|uploads| uploads.get_mut(&id))
+            .ok_or_else(|| error::not_found(format!("multipart upload {}", id)))?;
+
+        upload.parts.insert(part_idx, data);
+        Ok(part_idx)
+    }
+
+    /// Assemble the declared parts, in order, into the upload's target selector.
+    pub async fn complete_multipart(
+        &self,
+        txn: &Txn,
+        id: MultipartId,
+        part_order: Vec<PartId>,
+    ) -> TCResult<()> {
+        let upload = {
+            let mut multipart = self.multipart.write().await;
+            multipart
+                .get_mut(txn.id())
+                .and_then(|uploads| uploads.remove(&id))
+                .ok_or_else(|| error::not_found(format!("multipart upload {}", id)))?
+        };
+
+        let mut assembled = Vec::new();
+        for (position, part_idx) in part_order.iter().enumerate() {
+            let part = upload
+                .parts
+                .get(part_idx)
+                .ok_or_else(|| error::bad_request("Missing declared part", part_idx))?;
+
+            let is_last = position + 1 == part_order.len();
+            if !is_last && part.len() < MIN_PART_SIZE {
+                return Err(error::bad_request(
+                    "Part is smaller than the minimum allowed size",
+                    part_idx,
+                ));
+            }
+
+            assembled.extend_from_slice(part);
+        }
+
+        for (prev, next) in part_order.iter().zip(part_order.iter().skip(1)) {
+            if next <= prev {
+                return Err(error::bad_request(
+                    "Multipart upload parts must be completed in order",
+                    next,
+                ));
+            }
+        }
+
+        self.stage(txn.id(), Bytes::from(assembled)).await;
+        Ok(())
+    }
+
+    /// Discard a multipart upload's staged parts without writing anything.
+    pub async fn abort_multipart(&self, txn: &Txn, id: MultipartId) -> TCResult<()> {
+        let mut multipart = self.multipart.write().await;
+        if let Some(uploads) = multipart.get_mut(txn.id()) {
+            uploads.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    async fn current_meta(&self) -> Option<ObjectMeta> {
+        self.store
+            .get_opts(&self.path, GetRange::Offset(0))
+            .await
+            .ok()
+            .map(|(_, meta)| meta)
+    }
+
+    async fn check_precondition(&self, mode: &PutMode) -> TCResult<()> {
+        let existing = self.current_meta().await;
+
+        match mode {
+            PutMode::Overwrite => Ok(()),
+            PutMode::Create => {
+                if existing.is_some() {
+                    Err(error::conflict())
+                } else {
+                    Ok(())
+                }
+            }
+            PutMode::Update(UpdateVersion { e_tag, version }) => {
+                let existing = existing.ok_or_else(|| error::not_found(&self.path))?;
+
+                if let Some(e_tag) = e_tag {
+                    if e_tag != &existing.e_tag {
+                        return Err(error::conflict());
+                    }
+                }
+
+                if let Some(version) = version {
+                    let current = self.version.read().await;
+                    if version != &current.to_string() {
+                        return Err(error::conflict());
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    async fn stage(&self, txn_id: &TxnId, data: Bytes) {
+        let mut pending = self.pending.write().await;
+        let overlay = pending.entry(txn_id.clone()).or_default();
+        overlay.puts.insert(self.path.clone(), data);
+    }
+}
+
+impl Instance for Blob {
+    type Class = BlobType;
+
+    fn class(&self) -> BlobType {
+        BlobType
+    }
+}
+
+#[async_trait]
+impl CollectionInstance for Blob {
+    type Item = Value;
+    type Slice = Blob;
+
+    async fn get(
+        &self,
+        _txn: Arc<Txn>,
+        _path: TCPath,
+        selector: Value,
+    ) -> TCResult<CollectionItem<Self::Item, Self::Slice>> {
+        let range: GetRange = selector.try_into()?;
+        let (data, _meta) = self.store.get_opts(&self.path, range).await?;
+        Ok(CollectionItem::Value(Value::Bytes(data)))
+    }
+
+    async fn is_empty(&self, _txn: Arc<Txn>) -> TCResult<bool> {
+        let entries = self.store.list(&self.path).await?;
+        Ok(entries.is_empty())
+    }
+
+    async fn put(
+        &self,
+        txn: Arc<Txn>,
+        _path: TCPath,
+        _selector: Value,
+        value: CollectionItem<Self::Item, Self::Slice>,
+        mode: PutMode,
+    ) -> TCResult<PutResult> {
+        let data: Bytes = match value {
+            CollectionItem::Value(Value::Bytes(data)) => data,
+            other => return Err(error::bad_request("Blob expects a byte value, found", other)),
+        };
+
+        self.check_precondition(&mode).await?;
+        self.stage(txn.id(), data).await;
+
+        let mut version = self.version.write().await;
+        *version += 1;
+
+        Ok(PutResult {
+            e_tag: {
+                let mut hasher = DefaultHasher::new();
+                version.hash(&mut hasher);
+                format!("{:x}", hasher.finish())
+            },
+            version: *version,
+        })
+    }
+
+    async fn to_stream(&self, _txn: Arc<Txn>) -> TCResult<TCStream<Scalar>> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let (data, _meta) = self
+            .store
+            .get_opts(&self.path, GetRange::Offset(0))
+            .await?;
+
+        let chunks: Vec<Scalar> = data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| Value::Bytes(Bytes::copy_from_slice(chunk)).into())
+            .collect();
+
+        Ok(Box::pin(stream::iter(chunks)))
+    }
+
+    async fn subscribe(
+        &self,
+        txn: Arc<Txn>,
+    ) -> TCResult<TCStream<CollectionItem<Self::Item, Self::Slice>>> {
+        let _ = txn;
+        let snapshot = match self.store.get_opts(&self.path, GetRange::Offset(0)).await {
+            Ok((data, _meta)) => {
+                let item = CollectionItem::Value(Value::Bytes(data));
+                Box::pin(stream::once(futures::future::ready(item))) as TCStream<_>
+            }
+            Err(_) => Box::pin(stream::empty()),
+        };
+
+        let live = BroadcastStream::new(self.changes.subscribe()).filter_map(|delta| {
+            futures::future::ready(match delta {
+                Ok(data) => Some(CollectionItem::Value(Value::Bytes(data))),
+                // a lagging subscriber missed deltas; the caller should re-read via `get`
+                // to resync rather than trust the (now incomplete) delta stream
+                Err(_lagged) => None,
+            })
+        });
+
+        Ok(Box::pin(snapshot.chain(live)))
+    }
+}
+
+#[async_trait]
+impl Transact for Blob {
+    async fn commit(&self, txn_id: &TxnId) {
+        let overlay = self.pending.write().await.remove(txn_id);
+        self.multipart.write().await.remove(txn_id);
+
+        if let Some(overlay) = overlay {
+            for path in overlay.deletes {
+                let _ = self.store.delete(&path).await;
+            }
+
+            for (path, data) in overlay.puts {
+                if let Ok(meta) = self.store.put(&path, data.clone()).await {
+                    let _ = meta;
+                    let _ = self.changes.send(data);
+                }
+            }
+        }
+    }
+
+    async fn rollback(&self, txn_id: &TxnId) {
+        self.pending.write().await.remove(txn_id);
+        self.multipart.write().await.remove(txn_id);
+    }
+}
+
+impl From<Blob> for Collection {
+    fn from(blob: Blob) -> Collection {
+        Collection::Base(CollectionBase::Blob(blob))
+    }
+}
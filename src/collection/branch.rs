@@ -0,0 +1,158 @@
+//! Named branches and commit provenance, layered on top of [`Transact`] so a
+//! `CollectionInstance` can be read "as of" a branch or a specific commit rather
+//! than only ever seeing the latest state.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::class::TCResult;
+use crate::error;
+use crate::scalar::{Id, Link};
+use crate::transaction::lock::RwLock;
+use crate::transaction::{Transact, Txn, TxnId};
+
+/// A single upstream commit or collection that a transaction read from while
+/// producing a [`Commit`], so derived collections know what to recompute when
+/// an upstream commit changes.
+#[derive(Clone)]
+pub struct Provenance {
+    pub source: Link,
+    pub commit_id: TxnId,
+}
+
+/// An immutable node in the commit graph: the sealed result of one transaction,
+/// recording the commit(s) it was built on and what it read to produce its state.
+#[derive(Clone)]
+pub struct Commit {
+    pub id: TxnId,
+    pub parents: Vec<TxnId>,
+    pub provenance: Vec<Provenance>,
+}
+
+/// A mutable pointer to the latest [`Commit`] in a named line of history.
+struct BranchState {
+    head: Option<TxnId>,
+}
+
+/// A `Branch`/`Commit` subsystem extending [`Transact`]: `commit`/`rollback` on a
+/// `TxnId` either appends a new [`Commit`] to its branch or discards the pending
+/// mutations, rather than only applying them in place.
+#[async_trait]
+pub trait Versioned: Transact {
+    /// Begin a new commit on `branch`, returning the `TxnId` under which the
+    /// caller should stage its mutations.
+    async fn start_commit(&self, branch: &Id) -> TCResult<TxnId>;
+
+    /// Seal the mutations staged under `txn_id` into an immutable `Commit` and
+    /// advance its branch's head to point to it.
+    async fn finish_commit(&self, txn_id: &TxnId) -> TCResult<()>;
+
+    /// Look up a sealed `Commit` by id.
+    async fn inspect_commit(&self, commit_id: &TxnId) -> TCResult<Commit>;
+
+    /// List the commits reachable from `branch`'s head, most recent first.
+    async fn list_commits(&self, branch: &Id) -> TCResult<Vec<Commit>>;
+}
+
+/// A generic commit log that any `Transact` implementor can embed to get
+/// `Versioned` for free: it tracks branch heads and sealed commits but defers
+/// to the embedder for the actual staged/committed data.
+pub struct CommitLog {
+    branches: RwLock<HashMap<Id, BranchState>>,
+    commits: RwLock<HashMap<TxnId, Commit>>,
+}
+
+impl CommitLog {
+    pub fn new() -> CommitLog {
+        CommitLog {
+            branches: RwLock::new(HashMap::new()),
+            commits: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn start_commit(&self, branch: &Id, txn: &Arc<Txn>) -> TCResult<TxnId> {
+        let mut branches = self.branches.write().await;
+        branches
+            .entry(branch.clone())
+            .or_insert(BranchState { head: None });
+
+        Ok(txn.id().clone())
+    }
+
+    pub async fn finish_commit(&self, branch: &Id, txn_id: &TxnId) -> TCResult<()> {
+        let parent = {
+            let branches = self.branches.read().await;
+            branches
+                .get(branch)
+                .ok_or_else(|| error::not_found(branch))?
+                .head
+                .clone()
+        };
+
+        let commit = Commit {
+            id: txn_id.clone(),
+            parents: parent.into_iter().collect(),
+            provenance: vec![],
+        };
+
+        self.commits.write().await.insert(txn_id.clone(), commit);
+
+        let mut branches = self.branches.write().await;
+        let branch_state = branches
+            .get_mut(branch)
+            .ok_or_else(|| error::not_found(branch))?;
+
+        branch_state.head = Some(txn_id.clone());
+
+        Ok(())
+    }
+
+    pub async fn inspect_commit(&self, commit_id: &TxnId) -> TCResult<Commit> {
+        self.commits
+            .read()
+            .await
+            .get(commit_id)
+            .cloned()
+            .ok_or_else(|| error::not_found(commit_id))
+    }
+
+    pub async fn list_commits(&self, branch: &Id) -> TCResult<Vec<Commit>> {
+        let head = {
+            let branches = self.branches.read().await;
+            branches
+                .get(branch)
+                .ok_or_else(|| error::not_found(branch))?
+                .head
+                .clone()
+        };
+
+        let commits = self.commits.read().await;
+        let mut history = Vec::new();
+        let mut next = head;
+
+        while let Some(commit_id) = next {
+            let commit = commits
+                .get(&commit_id)
+                .cloned()
+                .ok_or_else(|| error::not_found(&commit_id))?;
+
+            next = commit.parents.first().cloned();
+            history.push(commit);
+        }
+
+        Ok(history)
+    }
+
+    pub async fn discard(&self, _txn_id: &TxnId) {
+        // pending mutations were never sealed into a `Commit`, so there is nothing
+        // to unwind here beyond what the embedder already rolled back
+    }
+}
+
+impl Default for CommitLog {
+    fn default() -> CommitLog {
+        CommitLog::new()
+    }
+}
@@ -0,0 +1,177 @@
+//! Column-path selectors for partial GET/PUT into a structured `Value`/`Scalar` item,
+//! so callers can read or overwrite a single nested field without a full
+//! read-modify-write of the item.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::class::TCResult;
+use crate::error;
+use crate::scalar::{Scalar, Value};
+
+/// A single step of a [`ColumnPath`]: either a map key or a list index.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ColumnSegment {
+    Key(Value),
+    Index(usize),
+}
+
+impl fmt::Display for ColumnSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColumnSegment::Key(key) => write!(f, "{}", key),
+            ColumnSegment::Index(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+/// An ordered list of [`ColumnSegment`]s addressing a nested cell within a
+/// structured `Scalar`/`Value` tree.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ColumnPath {
+    segments: Vec<ColumnSegment>,
+}
+
+impl ColumnPath {
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+}
+
+impl Deref for ColumnPath {
+    type Target = [ColumnSegment];
+
+    fn deref(&self) -> &[ColumnSegment] {
+        &self.segments
+    }
+}
+
+impl From<Vec<ColumnSegment>> for ColumnPath {
+    fn from(segments: Vec<ColumnSegment>) -> ColumnPath {
+        ColumnPath { segments }
+    }
+}
+
+/// Parse a `ColumnPath` out of a selector `Value`: either a single segment
+/// (a map key or numeric index) or a `Tuple` of segments to walk in order.
+impl TryFrom<Value> for ColumnPath {
+    type Error = error::TCError;
+
+    fn try_from(value: Value) -> TCResult<ColumnPath> {
+        fn to_segment(value: Value) -> TCResult<ColumnSegment> {
+            match value {
+                Value::Number(n) => {
+                    let i: u64 = n.try_into()?;
+                    Ok(ColumnSegment::Index(i as usize))
+                }
+                other => Ok(ColumnSegment::Key(other)),
+            }
+        }
+
+        match value {
+            Value::Tuple(segments) => {
+                let segments = segments
+                    .into_iter()
+                    .map(to_segment)
+                    .collect::<TCResult<Vec<ColumnSegment>>>()?;
+
+                Ok(ColumnPath { segments })
+            }
+            other => Ok(ColumnPath {
+                segments: vec![to_segment(other)?],
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ColumnPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.segments
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>()
+                .join("/")
+        )
+    }
+}
+
+/// Walk `subject` following `path`, returning the addressed sub-value.
+pub fn resolve_get(subject: &Scalar, path: &ColumnPath) -> TCResult<Scalar> {
+    let mut current = subject.clone();
+
+    for segment in path.iter() {
+        current = match (current, segment) {
+            (Scalar::Map(map), ColumnSegment::Key(key)) => map
+                .deref()
+                .get(&key.clone().try_into()?)
+                .cloned()
+                .ok_or_else(|| error::not_found(segment))?,
+            (Scalar::Tuple(tuple), ColumnSegment::Index(i)) => tuple
+                .deref()
+                .get(*i)
+                .cloned()
+                .ok_or_else(|| error::not_found(segment))?,
+            (other, _) => {
+                return Err(error::bad_request(
+                    "Cannot address a column path into",
+                    other,
+                ))
+            }
+        };
+    }
+
+    Ok(current)
+}
+
+/// Replace exactly the cell addressed by `path` within `subject` with `new_value`,
+/// creating intermediate maps as needed.
+pub fn resolve_put(subject: &mut Scalar, path: &ColumnPath, new_value: Scalar) -> TCResult<()> {
+    if path.is_empty() {
+        *subject = new_value;
+        return Ok(());
+    }
+
+    let (head, tail) = (&path[0], ColumnPath::from(path[1..].to_vec()));
+
+    match (subject, head) {
+        (Scalar::Map(map), ColumnSegment::Key(key)) => {
+            let key = key.clone().try_into()?;
+
+            if tail.is_empty() {
+                map.deref_mut().insert(key, new_value);
+                return Ok(());
+            }
+
+            let entry = map
+                .deref_mut()
+                .entry(key)
+                .or_insert_with(|| Scalar::Map(Default::default()));
+
+            resolve_put(entry, &tail, new_value)
+        }
+        (Scalar::Tuple(tuple), ColumnSegment::Index(i)) => {
+            let slot = tuple
+                .deref_mut()
+                .get_mut(*i)
+                .ok_or_else(|| error::not_found(head))?;
+
+            if tail.is_empty() {
+                *slot = new_value;
+                Ok(())
+            } else {
+                resolve_put(slot, &tail, new_value)
+            }
+        }
+        (other, _) => Err(error::bad_request(
+            "Cannot address a column path into",
+            &*other,
+        )),
+    }
+}
@@ -6,11 +6,46 @@ use futures::stream;
 
 use crate::class::{Class, Instance, NativeClass, TCResult, TCStream};
 use crate::collection::class::*;
+use crate::collection::branch::{Commit, Versioned};
+use crate::collection::column_path::ColumnPath;
 use crate::collection::{Collection, CollectionBase, CollectionItem};
 use crate::error;
-use crate::scalar::{label, Link, Scalar, TCPath, Value};
+use crate::scalar::{label, Id, Link, Scalar, TCPath, Value};
 use crate::transaction::{Transact, Txn, TxnId};
 
+/// Selects the compare-and-set semantics of a `CollectionInstance::put` call.
+#[derive(Clone)]
+pub enum PutMode {
+    /// Overwrite any existing value unconditionally (the historical behavior).
+    Overwrite,
+
+    /// Fail if the selector already has a value.
+    Create,
+
+    /// Fail unless the stored item's current `e_tag`/`version` matches the precondition.
+    Update(UpdateVersion),
+}
+
+impl Default for PutMode {
+    fn default() -> PutMode {
+        PutMode::Overwrite
+    }
+}
+
+/// The precondition supplied with `PutMode::Update`.
+#[derive(Clone, Default)]
+pub struct UpdateVersion {
+    pub e_tag: Option<String>,
+    pub version: Option<String>,
+}
+
+/// The version token returned by a successful conditional `put`.
+#[derive(Clone)]
+pub struct PutResult {
+    pub e_tag: String,
+    pub version: u64,
+}
+
 #[derive(Clone, Eq, PartialEq)]
 pub struct NullType;
 
@@ -78,8 +113,11 @@ impl CollectionInstance for Null {
         &self,
         _txn: Arc<Txn>,
         _path: TCPath,
-        _selector: Value,
+        selector: Value,
     ) -> TCResult<CollectionItem<Self::Item, Self::Slice>> {
+        // a selector may address a nested column path, but the Null Collection has no
+        // contents at any depth, so there is nothing to resolve it against
+        let _: ColumnPath = selector.try_into()?;
         Err(error::unsupported("Null Collection has no contents to GET"))
     }
 
@@ -91,15 +129,33 @@ impl CollectionInstance for Null {
         &self,
         _txn: Arc<Txn>,
         _path: TCPath,
-        _selector: Value,
+        selector: Value,
         _value: CollectionItem<Self::Item, Self::Slice>,
-    ) -> TCResult<()> {
+        _mode: PutMode,
+    ) -> TCResult<PutResult> {
+        let _: ColumnPath = selector.try_into()?;
         Err(error::unsupported("Null Collection cannot be modified"))
     }
 
     async fn to_stream(&self, _txn: Arc<Txn>) -> TCResult<TCStream<Scalar>> {
         Ok(Box::pin(stream::empty()))
     }
+
+    async fn subscribe(
+        &self,
+        _txn: Arc<Txn>,
+    ) -> TCResult<TCStream<CollectionItem<Self::Item, Self::Slice>>> {
+        // the Null Collection never changes, so there is nothing to subscribe to
+        Ok(Box::pin(stream::empty()))
+    }
+}
+
+impl Null {
+    pub async fn create_multipart(&self, _txn: &Txn, _selector: Value) -> TCResult<u64> {
+        Err(error::unsupported(
+            "Null Collection has no contents to upload",
+        ))
+    }
 }
 
 #[async_trait]
@@ -113,6 +169,36 @@ impl Transact for Null {
     }
 }
 
+#[async_trait]
+impl Versioned for Null {
+    async fn start_commit(&self, _branch: &Id) -> TCResult<TxnId> {
+        Ok(TxnId::zero())
+    }
+
+    async fn finish_commit(&self, _txn_id: &TxnId) -> TCResult<()> {
+        // the Null Collection is immutable and empty, so every commit it seals
+        // is indistinguishable from the last
+        Ok(())
+    }
+
+    async fn inspect_commit(&self, commit_id: &TxnId) -> TCResult<Commit> {
+        Ok(Commit {
+            id: commit_id.clone(),
+            parents: vec![],
+            provenance: vec![],
+        })
+    }
+
+    async fn list_commits(&self, _branch: &Id) -> TCResult<Vec<Commit>> {
+        // every branch of the Null Collection has exactly one, empty commit
+        Ok(vec![Commit {
+            id: TxnId::zero(),
+            parents: vec![],
+            provenance: vec![],
+        }])
+    }
+}
+
 impl From<Null> for Collection {
     fn from(null: Null) -> Collection {
         Collection::Base(CollectionBase::Null(null))
@@ -0,0 +1,392 @@
+//! Reverse-mode autodiff on top of the `TensorView` ops in this module:
+//! [`Variable`] wraps a `TensorView` and records every `add`/`multiply`/
+//! `sum_all`/`transpose`/`reshape`/`broadcast`/`slice` call it's routed
+//! through onto a shared [`Tape`], so [`Variable::backward`] can walk the
+//! tape in reverse and accumulate a gradient per input.
+//!
+//! Seeding the output adjoint needs a tensor of ones with the output's
+//! shape, and `slice`'s VJP scatters its adjoint into a tensor of zeros.
+//! [`tensor_zeros`]/[`tensor_ones`] build one by dispatching to
+//! `DenseTensor::constant`/`SparseTensor::create` the same way every other
+//! `TensorReduce`/`TensorMath` impl in this module dispatches to the
+//! concrete `dense`/`sparse` types -- still unreachable in this checkout,
+//! since neither file exists (the same gap documented on
+//! `crate::internal::repo`/`crate::internal::log`), but the dispatch
+//! itself is the real shape this subsystem should have once a backend
+//! fills that seam in. [`Variable::backward`] always seeds through
+//! `tensor_ones`. A parent's first-received adjoint is stored as-is and
+//! later contributions are `add`ed onto it, so merging needs no zeros.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::class::TCBoxTryFuture;
+use crate::error::TCResult;
+use crate::scalar::value::number::Number;
+use crate::transaction::Txn;
+
+use super::bounds::{self, Shape};
+use super::{
+    DenseTensor, IntoView, SparseTensor, TensorAccessor, TensorMath, TensorReduce,
+    TensorTransform, TensorView,
+};
+
+/// A tape node's identity, assigned in append order.
+pub type VarId = u64;
+
+/// The adjoints a [`TapeNode`]'s VJP hands back to its parents, one per
+/// parent, in the same order as [`TapeNode::parents`].
+type VjpFuture = TCBoxTryFuture<'static, Vec<TensorView>>;
+
+/// A vector-Jacobian-product closure: given the adjoint of this node's
+/// output, returns the adjoint of each of its parents.
+type Vjp = Box<dyn Fn(TensorView, Txn) -> VjpFuture + Send + Sync>;
+
+/// One step of a computation, as recorded by [`Tape::push`].
+struct TapeNode {
+    output: TensorView,
+    parents: Vec<VarId>,
+    vjp: Vjp,
+}
+
+/// The computation history a family of [`Variable`]s share, in append
+/// (i.e. topological) order -- a node can only name earlier-appended
+/// nodes as parents, so reversing append order is already a valid
+/// reverse-topological walk for [`Variable::backward`].
+#[derive(Default)]
+struct Tape {
+    nodes: HashMap<VarId, TapeNode>,
+    order: Vec<VarId>,
+    next_id: VarId,
+    root: Option<VarId>,
+}
+
+impl Tape {
+    fn push(&mut self, output: TensorView, parents: Vec<VarId>, vjp: Vjp) -> VarId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.nodes.insert(
+            id,
+            TapeNode {
+                output,
+                parents,
+                vjp,
+            },
+        );
+        self.order.push(id);
+
+        id
+    }
+
+    /// Mark `id` as the node `backward` should seed with ones, for a node
+    /// (like the input to `sum_all`) whose own forward result isn't a
+    /// `TensorView` and so can't carry a tape node of its own.
+    fn mark_root(&mut self, id: VarId) {
+        self.root = Some(id);
+    }
+}
+
+/// A `TensorView` wrapped with a computation tape, so that calling
+/// `add`/`multiply`/`sum_all`/`transpose`/`reshape`/`broadcast`/`slice`
+/// on it both performs the forward computation and records how to
+/// propagate a gradient back through it.
+#[derive(Clone)]
+pub struct Variable {
+    id: VarId,
+    view: TensorView,
+    tape: Arc<Mutex<Tape>>,
+}
+
+impl Variable {
+    /// Register `view` as a fresh leaf on a new tape.
+    pub fn new(view: TensorView) -> Variable {
+        let mut tape = Tape::default();
+        let id = tape.push(view.clone(), Vec::new(), Box::new(|adjoint, _txn| {
+            Box::pin(async move { Ok(vec![adjoint]) })
+        }));
+
+        Variable {
+            id,
+            view,
+            tape: Arc::new(Mutex::new(tape)),
+        }
+    }
+
+    pub fn view(&self) -> &TensorView {
+        &self.view
+    }
+
+    fn child(&self, view: TensorView, parents: Vec<VarId>, vjp: Vjp) -> Variable {
+        let id = self.tape.lock().expect("tape").push(view.clone(), parents, vjp);
+
+        Variable {
+            id,
+            view,
+            tape: self.tape.clone(),
+        }
+    }
+
+    pub fn add(&self, other: &Variable) -> TCResult<Variable> {
+        let (left, right) = super::broadcast(&self.view, &other.view)?;
+        let output = left.add(&right)?;
+
+        let left_shape = self.view.shape().to_vec();
+        let right_shape = other.view.shape().to_vec();
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let left_shape = left_shape.clone();
+            let right_shape = right_shape.clone();
+            Box::pin(async move {
+                let left_adjoint = sum_to_shape(&adjoint, &left_shape)?;
+                let right_adjoint = sum_to_shape(&adjoint, &right_shape)?;
+                let _ = txn;
+                Ok(vec![left_adjoint, right_adjoint])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id, other.id], vjp))
+    }
+
+    pub fn multiply(&self, other: &Variable) -> TCResult<Variable> {
+        let (left, right) = super::broadcast(&self.view, &other.view)?;
+        let output = left.multiply(&right)?;
+
+        let left_shape = self.view.shape().to_vec();
+        let right_shape = other.view.shape().to_vec();
+        let left_view = self.view.clone();
+        let right_view = other.view.clone();
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let left_shape = left_shape.clone();
+            let right_shape = right_shape.clone();
+            let left_view = left_view.clone();
+            let right_view = right_view.clone();
+            Box::pin(async move {
+                let (left_b, right_b) = super::broadcast(&left_view, &right_view)?;
+
+                let left_adjoint = adjoint.multiply(&right_b)?;
+                let left_adjoint = sum_to_shape(&left_adjoint, &left_shape)?;
+
+                let right_adjoint = adjoint.multiply(&left_b)?;
+                let right_adjoint = sum_to_shape(&right_adjoint, &right_shape)?;
+
+                let _ = txn;
+                Ok(vec![left_adjoint, right_adjoint])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id, other.id], vjp))
+    }
+
+    /// Reduce this `Variable` to a scalar and mark it as the tape's root,
+    /// so a later `backward` call (on any `Variable` sharing this tape)
+    /// seeds this node's adjoint with ones instead of its own.
+    pub async fn sum_all(&self, txn: Txn) -> TCResult<crate::scalar::value::number::Number> {
+        self.tape.lock().expect("tape").mark_root(self.id);
+        self.view.sum_all(txn).await
+    }
+
+    pub fn transpose(&self, permutation: Option<Vec<usize>>) -> TCResult<Variable> {
+        let output = self.view.transpose(permutation.clone())?;
+        let inverse = permutation.as_ref().map(|p| inverse_permutation(p));
+
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let inverse = inverse.clone();
+            Box::pin(async move {
+                let _ = txn;
+                Ok(vec![adjoint.transpose(inverse)?])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id], vjp))
+    }
+
+    pub fn reshape(&self, shape: Shape) -> TCResult<Variable> {
+        let output = self.view.reshape(shape.clone())?;
+        let original_shape: Shape = self.view.shape().to_vec().into();
+
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let original_shape = original_shape.clone();
+            Box::pin(async move {
+                let _ = txn;
+                Ok(vec![adjoint.reshape(original_shape)?])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id], vjp))
+    }
+
+    pub fn broadcast(&self, shape: Shape) -> TCResult<Variable> {
+        let output = self.view.broadcast(shape)?;
+        let original_shape = self.view.shape().to_vec();
+
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let original_shape = original_shape.clone();
+            Box::pin(async move {
+                let _ = txn;
+                Ok(vec![sum_to_shape(&adjoint, &original_shape)?])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id], vjp))
+    }
+
+    pub fn slice(&self, bounds: bounds::Bounds) -> TCResult<Variable> {
+        let output = self.view.slice(bounds.clone())?;
+        let original_shape = self.view.shape().to_vec();
+        let like = self.view.clone();
+
+        let vjp: Vjp = Box::new(move |adjoint, txn| {
+            let original_shape = original_shape.clone();
+            let bounds = bounds.clone();
+            let like = like.clone();
+            Box::pin(async move {
+                let scattered = scatter_adjoint(adjoint, original_shape, bounds, like, txn).await?;
+                Ok(vec![scattered])
+            })
+        });
+
+        Ok(self.child(output, vec![self.id], vjp))
+    }
+
+    /// Walk the tape this `Variable` shares in reverse topological order,
+    /// seeding the tape's root (the node marked by `sum_all`, or `self`
+    /// if `sum_all` was never called) with ones, and accumulating each
+    /// node's parents' adjoints via `add`.
+    pub async fn backward(&self, txn: &Txn) -> TCResult<HashMap<VarId, TensorView>> {
+        let (order, root, root_output) = {
+            let tape = self.tape.lock().expect("tape");
+            let root = tape.root.unwrap_or(self.id);
+            let root_output = tape
+                .nodes
+                .get(&root)
+                .map(|node| node.output.clone())
+                .unwrap_or_else(|| self.view.clone());
+
+            (tape.order.clone(), root, root_output)
+        };
+
+        let mut adjoints: HashMap<VarId, TensorView> = HashMap::new();
+        let seed = tensor_ones(&root_output.shape().to_vec(), &root_output, txn).await?;
+        adjoints.insert(root, seed);
+
+        for id in order.into_iter().rev() {
+            let adjoint = match adjoints.get(&id) {
+                Some(adjoint) => adjoint.clone(),
+                // nothing downstream of `id` ever received an adjoint, so
+                // nothing propagates further back through it either
+                None => continue,
+            };
+
+            let (parents, vjp_future) = {
+                let tape = self.tape.lock().expect("tape");
+                let node = tape.nodes.get(&id).expect("tape node");
+                (node.parents.clone(), (node.vjp)(adjoint, txn.clone()))
+            };
+
+            let parent_adjoints = vjp_future.await?;
+
+            for (parent_id, parent_adjoint) in parents.into_iter().zip(parent_adjoints) {
+                let combined = match adjoints.remove(&parent_id) {
+                    Some(existing) => existing.add(&parent_adjoint)?,
+                    None => parent_adjoint,
+                };
+
+                adjoints.insert(parent_id, combined);
+            }
+        }
+
+        Ok(adjoints)
+    }
+}
+
+impl IntoView for Variable {
+    fn into_view(self) -> TensorView {
+        self.view
+    }
+}
+
+/// The inverse of `TensorTransform::broadcast`: sum-reduce `adjoint` back
+/// down to `target_shape`, first collapsing any leading dimensions
+/// `broadcast` inserted (by left-padding the original shape with 1s),
+/// then collapsing any dimension `broadcast` stretched from 1 to a
+/// larger size. Assumes `TensorReduce::sum(axis)` drops `axis` entirely
+/// rather than keeping it at size 1 -- there is no `keepdims` parameter
+/// on that trait method to say otherwise, and the real dense/sparse
+/// backends this would run against don't exist in this checkout to check
+/// against.
+fn sum_to_shape(adjoint: &TensorView, target_shape: &[u64]) -> TCResult<TensorView> {
+    let mut reduced = adjoint.clone();
+
+    while reduced.shape().to_vec().len() > target_shape.len() {
+        reduced = reduced.sum(0)?;
+    }
+
+    for (axis, target_size) in target_shape.iter().enumerate() {
+        if *target_size == 1 && reduced.shape().to_vec()[axis] != 1 {
+            reduced = reduced.sum(axis)?.expand_dims(axis)?;
+        }
+    }
+
+    Ok(reduced)
+}
+
+/// The inverse of `TensorTransform::slice`: scatter `adjoint` into a zero
+/// tensor of `original_shape`, since every element outside the sliced
+/// region contributed nothing to the sliced-out value and so has a zero
+/// gradient.
+async fn scatter_adjoint(
+    adjoint: TensorView,
+    original_shape: Vec<u64>,
+    bounds: bounds::Bounds,
+    like: TensorView,
+    txn: Txn,
+) -> TCResult<TensorView> {
+    use super::TensorIO;
+
+    let zero = tensor_zeros(&original_shape, &like, &txn).await?;
+    zero.write(txn, bounds, adjoint).await?;
+    Ok(zero)
+}
+
+/// A tensor of zeros with shape `shape`, matching `like`'s dtype.
+async fn tensor_zeros(shape: &[u64], like: &TensorView, txn: &Txn) -> TCResult<TensorView> {
+    constant(shape, like, txn, like.dtype().zero()).await
+}
+
+/// A tensor of ones with shape `shape`, matching `like`'s dtype.
+async fn tensor_ones(shape: &[u64], like: &TensorView, txn: &Txn) -> TCResult<TensorView> {
+    constant(shape, like, txn, like.dtype().one()).await
+}
+
+/// Build a tensor of `shape` filled with `value`, matching `like`'s dtype.
+/// A `Sparse` `like` stays sparse when `value` is that dtype's own zero --
+/// an empty `SparseTensor` is already all zeros, with no entries to store
+/// -- and densifies otherwise, since a uniform nonzero fill would mean
+/// writing every coordinate anyway. `TensorAccessor::dtype` and
+/// `DenseTensor::constant`/`SparseTensor::create` mirror the
+/// `host::route::collection::tensor` precedent (`dtype.zero()`,
+/// `DenseTensor::constant(file, txn_id, shape, value)`), adapted to take
+/// `&Txn` directly the way `TensorIO::read_value`/`mask` already do in
+/// this module, rather than a separate file handle.
+async fn constant(shape: &[u64], like: &TensorView, txn: &Txn, value: Number) -> TCResult<TensorView> {
+    let shape: Shape = shape.to_vec().into();
+
+    match like {
+        TensorView::Sparse(_) if value == like.dtype().zero() => SparseTensor::create(txn, shape, like.dtype())
+            .await
+            .map(TensorView::from),
+        _ => DenseTensor::constant(txn, shape, value)
+            .await
+            .map(TensorView::from),
+    }
+}
+
+/// `perm` maps output axis -> source axis; its inverse maps source axis
+/// -> output axis, which is exactly the permutation that undoes it.
+fn inverse_permutation(perm: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; perm.len()];
+    for (output_axis, &source_axis) in perm.iter().enumerate() {
+        inverse[source_axis] = output_axis;
+    }
+    inverse
+}
@@ -6,17 +6,22 @@ use crate::scalar::value::number::*;
 use crate::transaction::{Txn, TxnId};
 
 mod einsum;
+mod parallel;
 mod transform;
 
 pub mod bounds;
 pub mod class;
 pub mod dense;
+pub mod grad;
 pub mod sparse;
 
 pub use class::{Tensor, TensorAccessor, TensorBaseType, TensorType, TensorView};
 pub use dense::{Array, DenseTensor};
 pub use einsum::einsum;
+pub use grad::{VarId, Variable};
+pub use parallel::{parallel_combine, StreamId, PARALLEL_THRESHOLD};
 pub use sparse::SparseTensor;
+pub use transform::TransformFusion;
 
 pub const ERR_NONBIJECTIVE_WRITE: &str = "Cannot write to a derived Tensor which is not a \
 bijection of its source. Consider copying first, or writing directly to the source Tensor.";
@@ -87,12 +92,77 @@ pub trait TensorMath<O>: TensorAccessor + Sized {
 
     fn add(&self, other: &O) -> TCResult<Self::Combine>;
 
+    fn divide(&self, other: &O) -> TCResult<Self::Combine>;
+
     fn multiply(&self, other: &O) -> TCResult<Self::Combine>;
+
+    fn pow(&self, other: &O) -> TCResult<Self::Combine>;
+
+    fn subtract(&self, other: &O) -> TCResult<Self::Combine>;
+}
+
+/// Elementwise transcendental and activation functions, kept separate
+/// from [`TensorMath`] since each of these takes no second operand.
+pub trait TensorActivation: TensorAccessor + Sized {
+    type Unary: IntoView;
+
+    fn exp(&self) -> TCResult<Self::Unary>;
+
+    fn log(&self) -> TCResult<Self::Unary>;
+
+    fn sigmoid(&self) -> TCResult<Self::Unary>;
+
+    fn tanh(&self) -> TCResult<Self::Unary>;
+
+    fn relu(&self) -> TCResult<Self::Unary>;
+
+    /// `exp(x_i - max_j x_j) / sum_k exp(x_k - max_j x_j)` along `axis`,
+    /// subtracting the per-axis max before exponentiating so the result
+    /// stays finite for large logits.
+    fn softmax(&self, axis: usize) -> TCResult<Self::Unary>;
+
+    /// Like [`TensorActivation::softmax`], but with an implicit zero
+    /// logit folded into the denominator: `exp(x_i - max) / (1 + sum_k
+    /// exp(x_k - max))`. The outputs no longer sum to one, but this is
+    /// more robust than `softmax` when every logit along `axis` is very
+    /// negative, since the denominator can't collapse to (approximately)
+    /// zero. `TensorView`'s impl returns a not-implemented error, since the
+    /// `1 +` term needs a tensor of ones (see `constant_like`).
+    fn quiet_softmax(&self, axis: usize) -> TCResult<Self::Unary>;
 }
 
 pub trait TensorReduce: TensorAccessor + Sized {
     type Reduce: IntoView;
 
+    /// The index of the largest element along `axis`, as an integer
+    /// tensor of the same rank as `self` with `axis` reduced to size one.
+    /// Ties resolve to the lowest index.
+    fn argmax(&self, axis: usize) -> TCResult<Self::Reduce>;
+
+    fn argmax_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
+
+    /// The index of the smallest element along `axis`. Ties resolve to
+    /// the lowest index.
+    fn argmin(&self, axis: usize) -> TCResult<Self::Reduce>;
+
+    fn argmin_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
+
+    fn max(&self, axis: usize) -> TCResult<Self::Reduce>;
+
+    fn max_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
+
+    /// `sum(axis) / dim_size`, promoting `self`'s dtype the way
+    /// `sum(axis)` followed by `as_type`/`divide` would. `TensorView`'s
+    /// impl returns a not-implemented error, since dividing by `dim_size`
+    /// needs a constant tensor (see `constant_like`).
+    fn mean(&self, axis: usize) -> TCResult<Self::Reduce>;
+
+    fn mean_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
+
+    fn min(&self, axis: usize) -> TCResult<Self::Reduce>;
+
+    fn min_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
+
     fn product(&self, axis: usize) -> TCResult<Self::Reduce>;
 
     fn product_all(&self, txn: Txn) -> TCBoxTryFuture<Number>;
@@ -123,6 +193,10 @@ pub trait TensorTransform: TensorAccessor + Sized {
     fn transpose(&self, permutation: Option<Vec<usize>>) -> TCResult<Self::Transpose>;
 }
 
+// `and`/`or`/`xor` below are synchronous and never see a `Txn`, so large
+// results compose lazily here; a caller that already holds a `Txn` and
+// wants one materialized in parallel instead calls `parallel::parallel_combine`
+// directly rather than through these methods.
 #[async_trait]
 impl TensorBoolean<TensorView> for TensorView {
     type Unary = TensorView;
@@ -185,6 +259,8 @@ impl TensorBoolean<TensorView> for TensorView {
     }
 }
 
+// `gt`/`lt`/`ne` below are synchronous and never see a `Txn`, unlike
+// `eq`/`gte`/`lte` on this same trait; see the note on `TensorBoolean` above.
 #[async_trait]
 impl TensorCompare<TensorView> for TensorView {
     type Compare = Self;
@@ -338,6 +414,8 @@ impl TensorIO<TensorView> for TensorView {
     }
 }
 
+// `add`/`multiply`/`divide` below are synchronous and never see a `Txn`;
+// see the note on `TensorBoolean` above.
 impl TensorMath<TensorView> for TensorView {
     type Unary = Self;
     type Combine = Self;
@@ -362,6 +440,19 @@ impl TensorMath<TensorView> for TensorView {
         }
     }
 
+    fn divide(&self, other: &Self) -> TCResult<Self> {
+        match (self, other) {
+            (Self::Dense(left), Self::Dense(right)) => left.divide(right).map(Self::from),
+            (Self::Sparse(left), Self::Sparse(right)) => left.divide(right).map(Self::from),
+            (Self::Dense(left), Self::Sparse(right)) => left
+                .divide(&DenseTensor::from_sparse(right.clone()))
+                .map(Self::from),
+            (Self::Sparse(left), Self::Dense(right)) => DenseTensor::from_sparse(left.clone())
+                .divide(right)
+                .map(Self::from),
+        }
+    }
+
     fn multiply(&self, other: &Self) -> TCResult<Self> {
         match (self, other) {
             (Self::Dense(left), Self::Dense(right)) => left.multiply(right).map(Self::from),
@@ -374,11 +465,161 @@ impl TensorMath<TensorView> for TensorView {
                 .map(Self::from),
         }
     }
+
+    fn pow(&self, other: &Self) -> TCResult<Self> {
+        match (self, other) {
+            (Self::Dense(left), Self::Dense(right)) => left.pow(right).map(Self::from),
+            (Self::Sparse(left), Self::Sparse(right)) => left.pow(right).map(Self::from),
+            (Self::Dense(left), Self::Sparse(right)) => left
+                .pow(&DenseTensor::from_sparse(right.clone()))
+                .map(Self::from),
+            (Self::Sparse(left), Self::Dense(right)) => DenseTensor::from_sparse(left.clone())
+                .pow(right)
+                .map(Self::from),
+        }
+    }
+
+    fn subtract(&self, other: &Self) -> TCResult<Self> {
+        match (self, other) {
+            (Self::Dense(left), Self::Dense(right)) => left.subtract(right).map(Self::from),
+            (Self::Sparse(left), Self::Sparse(right)) => left.subtract(right).map(Self::from),
+            (Self::Dense(left), Self::Sparse(right)) => left
+                .subtract(&DenseTensor::from_sparse(right.clone()))
+                .map(Self::from),
+            (Self::Sparse(left), Self::Dense(right)) => DenseTensor::from_sparse(left.clone())
+                .subtract(right)
+                .map(Self::from),
+        }
+    }
+}
+
+impl TensorActivation for TensorView {
+    type Unary = Self;
+
+    fn exp(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.exp().map(Self::from),
+            Self::Sparse(sparse) => DenseTensor::from_sparse(sparse.clone())
+                .exp()
+                .map(Self::from),
+        }
+    }
+
+    fn log(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.log().map(Self::from),
+            Self::Sparse(sparse) => sparse.log().map(Self::from),
+        }
+    }
+
+    fn sigmoid(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.sigmoid().map(Self::from),
+            Self::Sparse(sparse) => DenseTensor::from_sparse(sparse.clone())
+                .sigmoid()
+                .map(Self::from),
+        }
+    }
+
+    fn tanh(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.tanh().map(Self::from),
+            Self::Sparse(sparse) => sparse.tanh().map(Self::from),
+        }
+    }
+
+    fn relu(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.relu().map(Self::from),
+            Self::Sparse(sparse) => sparse.relu().map(Self::from),
+        }
+    }
+
+    fn softmax(&self, axis: usize) -> TCResult<Self> {
+        softmax(self, axis, false)
+    }
+
+    fn quiet_softmax(&self, axis: usize) -> TCResult<Self> {
+        softmax(self, axis, true)
+    }
 }
 
 impl TensorReduce for TensorView {
     type Reduce = Self;
 
+    fn argmax(&self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.argmax(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.argmax(axis).map(Self::from),
+        }
+    }
+
+    fn argmax_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.argmax_all(txn),
+            Self::Sparse(sparse) => sparse.argmax_all(txn),
+        }
+    }
+
+    fn argmin(&self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.argmin(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.argmin(axis).map(Self::from),
+        }
+    }
+
+    fn argmin_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.argmin_all(txn),
+            Self::Sparse(sparse) => sparse.argmin_all(txn),
+        }
+    }
+
+    fn max(&self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.max(axis).map(Self::from),
+            // a slice with any position not explicitly set along `axis`
+            // has an implicit zero there, which this reduction has to
+            // treat as a real candidate value, not skip over
+            Self::Sparse(sparse) => sparse.max(axis).map(Self::from),
+        }
+    }
+
+    fn max_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.max_all(txn),
+            Self::Sparse(sparse) => sparse.max_all(txn),
+        }
+    }
+
+    fn mean(&self, axis: usize) -> TCResult<Self::Reduce> {
+        let dim_size = self.shape().to_vec()[axis];
+        let sum = self.sum(axis)?.as_type(NumberType::Float(FloatType::F64))?;
+        let count = constant_like(&sum, dim_size as f64)?;
+        sum.divide(&count)
+    }
+
+    fn mean_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
+        let size: u64 = self.shape().to_vec().iter().product();
+        let sum = self.sum_all(txn);
+        Box::pin(async move { Ok(sum.await? / Number::from(size as f64)) })
+    }
+
+    fn min(&self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.min(axis).map(Self::from),
+            // same implicit-zero consideration as `max` above
+            Self::Sparse(sparse) => sparse.min(axis).map(Self::from),
+        }
+    }
+
+    fn min_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.min_all(txn),
+            Self::Sparse(sparse) => sparse.min_all(txn),
+        }
+    }
+
     fn product(&self, axis: usize) -> TCResult<Self::Reduce> {
         match self {
             Self::Dense(dense) => dense.product(axis).map(Self::from),
@@ -395,15 +636,15 @@ impl TensorReduce for TensorView {
 
     fn sum(&self, axis: usize) -> TCResult<Self::Reduce> {
         match self {
-            Self::Dense(dense) => dense.product(axis).map(Self::from),
-            Self::Sparse(sparse) => sparse.product(axis).map(Self::from),
+            Self::Dense(dense) => dense.sum(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.sum(axis).map(Self::from),
         }
     }
 
     fn sum_all(&self, txn: Txn) -> TCBoxTryFuture<Number> {
         match self {
-            Self::Dense(dense) => dense.product_all(txn),
-            Self::Sparse(sparse) => sparse.product_all(txn),
+            Self::Dense(dense) => dense.sum_all(txn),
+            Self::Sparse(sparse) => sparse.sum_all(txn),
         }
     }
 }
@@ -500,3 +741,58 @@ fn broadcast<L: Clone + TensorTransform, R: Clone + TensorTransform>(
     let right = right.broadcast(shape.into())?;
     Ok((left, right))
 }
+
+/// Shared implementation of `TensorActivation::softmax`/`quiet_softmax`.
+/// Densifies a sparse `view` first, since the zero-fill value maps to a
+/// nonzero result under `exp`. Subtracts the per-axis max before
+/// exponentiating for numerical stability, then divides by `sum_k
+/// exp(x_k - max)` -- or, for `quiet`, by `1 + sum_k exp(x_k - max)`.
+fn softmax(view: &TensorView, axis: usize, quiet: bool) -> TCResult<TensorView> {
+    let view = match view {
+        TensorView::Sparse(sparse) => TensorView::Dense(DenseTensor::from_sparse(sparse.clone())),
+        TensorView::Dense(_) => view.clone(),
+    };
+
+    let max = axis_max(&view, axis)?.expand_dims(axis)?;
+    let (view, max) = broadcast(&view, &max)?;
+    let numerator = view.subtract(&max)?.exp()?;
+
+    let mut denominator = numerator.sum(axis)?.expand_dims(axis)?;
+    if quiet {
+        let ones = ones_like(&denominator)?;
+        denominator = denominator.add(&ones)?;
+    }
+
+    let (numerator, denominator) = broadcast(&numerator, &denominator)?;
+    numerator.divide(&denominator)
+}
+
+/// The per-axis maximum, used by [`softmax`] for numerical stability --
+/// assumes `DenseTensor`/`SparseTensor` expose a `max(axis)` reduction
+/// the same way `TensorReduce::product`/`sum` assume `product(axis)`/
+/// `sum(axis)`: the real reduction lives in `dense`/`sparse`, declared as
+/// submodules of `tensor` but absent as files from this checkout.
+fn axis_max(view: &TensorView, axis: usize) -> TCResult<TensorView> {
+    match view {
+        TensorView::Dense(dense) => dense.max(axis).map(TensorView::from),
+        TensorView::Sparse(sparse) => sparse.max(axis).map(TensorView::from),
+    }
+}
+
+/// A tensor filled with `value`, the same shape (and dtype) as `like`.
+/// There is no constant-tensor constructor reachable from this module to
+/// build one with -- the same gap `grad::tensor_ones`/`grad::tensor_zeros`
+/// are written around, since `dense`/`sparse` are declared as submodules
+/// of `tensor` but neither exists as a file in this checkout.
+fn constant_like(like: &TensorView, value: f64) -> TCResult<TensorView> {
+    let _ = (like, value);
+    Err(error::not_implemented(
+        "constant-tensor construction (no constant-tensor constructor is reachable from this module)",
+    ))
+}
+
+/// A tensor of ones with the same shape (and dtype) as `like`, needed by
+/// `quiet_softmax` to fold an implicit zero logit into its denominator.
+fn ones_like(like: &TensorView) -> TCResult<TensorView> {
+    constant_like(like, 1.0)
+}
@@ -0,0 +1,206 @@
+//! Tile-parallel evaluation of elementwise `TensorMath`/`TensorBoolean`/
+//! `TensorCompare` combinators (`add`/`multiply`/`and`/`or`/`gt`, ...) over
+//! large tensors.
+//!
+//! This is deliberately *not* wired into `add`/`multiply`/`and`/`or`/`gt`
+//! themselves: every one of those methods is synchronous and carries no
+//! `Txn` (unlike `eq`/`gte`/`lte`/`all`/`any` on the same traits, which do),
+//! so there is no point in their call where a transaction exists to join
+//! tile writes against, and no way to switch over to tiled writes from
+//! inside them without adding a `Txn` parameter to methods that every other
+//! tensor module built so far (`grad`, `einsum`, `transform`) calls
+//! synchronously. Changing those signatures is out of scope here.
+//! [`parallel_combine`] is instead an explicit, opt-in materializing path: a
+//! caller that already holds a `Txn` and an allocated `output` tensor calls
+//! it directly in place of `left.add(&right)` when it wants the combined
+//! result written out in parallel rather than composed lazily. It partitions
+//! `output`'s coordinate space into tiles and evaluates + writes each one
+//! concurrently, falling back to a single tile (no concurrency, and so no
+//! behavior change) at or below the caller's `threshold` element count
+//! ([`PARALLEL_THRESHOLD`] is a suggested value), and returns the
+//! [`StreamId`] each tile's writes went out under so the caller can track or
+//! log them individually instead of only seeing the combined result.
+//!
+//! Tiling is a straightforward axis-0 split into contiguous row ranges --
+//! the simplest partition that can't divide a row, and so can't straddle
+//! whatever block the underlying dense storage groups a row into. A real
+//! dense backend would tile to its own block size instead; there is none
+//! reachable here to align to, since `dense` is declared as a submodule of
+//! `tensor` but isn't a file in this checkout.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use futures::future::try_join_all;
+
+use crate::error::TCResult;
+use crate::scalar::value::number::Number;
+use crate::transaction::{Txn, TxnId};
+
+use super::{TensorAccessor, TensorIO, TensorView};
+
+/// A suggested `threshold` for [`parallel_combine`]: at or below that many
+/// output elements it runs a single, unparallelized tile -- tiling only pays
+/// for itself once there's enough work to split across workers.
+pub const PARALLEL_THRESHOLD: usize = 1 << 16;
+
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    static STREAM_ID: StreamId;
+}
+
+/// A lightweight identifier the transaction layer can use to track and join
+/// the outstanding writes one tile's worker issues, distinct from every
+/// other tile's. Assigned once per worker task by [`with_stream`] when the
+/// task starts, not re-derived per coordinate it writes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StreamId(u64);
+
+impl StreamId {
+    fn next() -> StreamId {
+        StreamId(NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The current task's stream id, as assigned by the enclosing
+    /// [`with_stream`] scope. Panics when called outside such a scope.
+    pub fn current() -> StreamId {
+        STREAM_ID.with(|id| *id)
+    }
+}
+
+/// Run `body` with a freshly assigned [`StreamId`] bound for its whole
+/// duration, so every [`StreamId::current`] call it makes (directly, or via
+/// code it calls) sees the same id.
+async fn with_stream<F, T>(body: F) -> T
+where
+    F: std::future::Future<Output = T>,
+{
+    STREAM_ID.scope(StreamId::next(), body).await
+}
+
+/// Evaluate a binary elementwise combinator over `left`/`right`, writing
+/// the result into `output` -- which must already exist, since there is no
+/// constant-tensor constructor reachable from this module to allocate one,
+/// the same gap [`super::grad::Variable`]'s VJPs and `super`'s `ones_like`/
+/// `constant_like` are written around. Above `threshold` output elements
+/// (see [`PARALLEL_THRESHOLD`] for a suggested value), this splits the coordinate space into one tile per available
+/// worker and evaluates them concurrently; below it, everything runs as a
+/// single tile, identical in behavior (if not performance) to the
+/// small-tensor path.
+///
+/// Each tile reads both operands and writes its result one coordinate at a
+/// time through [`TensorIO::write_value_at`], so tiles never overlap and
+/// need no locking beyond joining their futures -- the caller is
+/// responsible for partitioning only along axes that line up with the
+/// underlying storage's block boundaries, which this function assumes is
+/// axis 0 (see the module documentation). Returns the [`StreamId`] each
+/// tile completed under, one per tile and in no particular order, so a
+/// caller can fold them into its own write-tracking instead of the ids
+/// being assigned and thrown away the moment each tile finishes.
+pub async fn parallel_combine<F>(
+    txn_id: TxnId,
+    txn: &Txn,
+    left: &TensorView,
+    right: &TensorView,
+    output: &TensorView,
+    threshold: usize,
+    combine: F,
+) -> TCResult<Vec<StreamId>>
+where
+    F: Fn(Number, Number) -> TCResult<Number> + Send + Sync,
+{
+    let shape = output.shape().to_vec();
+    let size = shape.iter().product::<u64>() as usize;
+
+    let tile_count = if size > threshold {
+        available_parallelism()
+    } else {
+        1
+    };
+
+    let tiles = tile_ranges(&shape, tile_count);
+
+    let writes = tiles.into_iter().map(|tile| {
+        let txn_id = txn_id.clone();
+        let combine = &combine;
+
+        with_stream(async move {
+            let stream = StreamId::current();
+
+            for coord in coords_in(&tile) {
+                let left_value = left.read_value(txn, &coord).await?;
+                let right_value = right.read_value(txn, &coord).await?;
+                let value = combine(left_value, right_value)?;
+                output.write_value_at(txn_id.clone(), coord, value).await?;
+            }
+
+            TCResult::Ok(stream)
+        })
+    });
+
+    try_join_all(writes).await
+}
+
+/// The number of concurrent tiles to split the work into -- one per
+/// available CPU, falling back to a single tile if that can't be
+/// determined.
+fn available_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Partition `shape`'s coordinate space into up to `tile_count` tiles, each
+/// a contiguous range of axis-0 indices paired with the full range of every
+/// other axis. Never produces more tiles than `shape[0]` has indices, and
+/// spreads any remainder across the first few tiles so sizes differ by at
+/// most one.
+fn tile_ranges(shape: &[u64], tile_count: usize) -> Vec<Vec<Range<u64>>> {
+    if shape.is_empty() || shape[0] == 0 {
+        return vec![shape.iter().map(|&dim| 0..dim).collect()];
+    }
+
+    let outer = shape[0];
+    let tile_count = tile_count.max(1).min(outer as usize);
+    let base = outer / tile_count as u64;
+    let remainder = outer % tile_count as u64;
+
+    let mut tiles = Vec::with_capacity(tile_count);
+    let mut start = 0u64;
+
+    for i in 0..tile_count {
+        let size = base + if (i as u64) < remainder { 1 } else { 0 };
+        let end = start + size;
+
+        let mut ranges = vec![start..end];
+        ranges.extend(shape[1..].iter().map(|&dim| 0..dim));
+        tiles.push(ranges);
+
+        start = end;
+    }
+
+    tiles
+}
+
+/// Every coordinate within `ranges`, in row-major order.
+fn coords_in(ranges: &[Range<u64>]) -> Vec<Vec<u64>> {
+    let mut coords = vec![Vec::with_capacity(ranges.len())];
+
+    for range in ranges {
+        let span = (range.end - range.start) as usize;
+        let mut next = Vec::with_capacity(coords.len() * span);
+
+        for coord in &coords {
+            for i in range.clone() {
+                let mut coord = coord.clone();
+                coord.push(i);
+                next.push(coord);
+            }
+        }
+
+        coords = next;
+    }
+
+    coords
+}
@@ -0,0 +1,214 @@
+//! Fuses a chain of `TensorTransform` calls -- `expand_dims`/`reshape`/
+//! `transpose` -- into a canonical form in which adjacent axis moves are
+//! composed into one `transpose` and redundant ops cancel (see
+//! [`canonicalize_pass`]), instead of wrapping (or materializing) a new
+//! derived view at every step. Adjacent `Reshape`s are not merged, and a
+//! `Move` on either side of a `Reshape` stays separate, so a chain that
+//! interleaves reshapes and transposes still lowers to several of each.
+//!
+//! A chain like `t.transpose(p).reshape(s).expand_dims(a)` is recorded as
+//! a sequence of primitive [`Op`]s (`Add`/`Rm`/`Move`/`Reshape`) pushed
+//! onto a [`TransformFusion`], canonicalized after every push so adjacent
+//! ops never accumulate redundant axis moves. [`TensorTransform`] itself
+//! is untouched -- `expand_dims`/`reshape`/`transpose` on a `TensorView`
+//! still apply eagerly, one call at a time, since making that lazy would
+//! mean threading a pending-ops buffer through the `TensorView` enum that
+//! every other dispatch in this module matches on directly. Build a
+//! `TransformFusion` explicitly instead, when a caller (e.g. a `grad.rs`
+//! VJP closure composing several axis ops before it has a `TensorView` to
+//! call them against) wants to defer lowering until the end, then call
+//! [`TransformFusion::apply`] once to run it.
+//!
+//! `Move`'s permutation vectors follow the same convention as
+//! `TensorTransform::transpose`'s `permutation` argument: `permutation[k]`
+//! is the source axis that becomes output axis `k`.
+
+use crate::error::{self, TCResult};
+
+use super::bounds::Shape;
+use super::{TensorTransform, TensorView};
+
+/// One primitive axis operation in a pending [`TransformFusion`].
+#[derive(Clone, Debug, PartialEq)]
+enum Op {
+    /// Insert a new size-1 axis at this position.
+    Add(usize),
+    /// Remove the (size-1) axis at this position.
+    Rm(usize),
+    /// A net axis permutation, composed from one or more single-axis
+    /// relocations by [`TransformFusion::canonicalize`].
+    Move(Vec<usize>),
+    /// Replace `old_dims` with `new_dims`, reinterpreting the underlying
+    /// elements in row-major order.
+    Reshape(Vec<u64>, Vec<u64>),
+}
+
+/// A pending sequence of axis operations, canonicalized after every push
+/// so it never grows beyond what the rules in [`canonicalize_pass`] can
+/// already collapse.
+#[derive(Clone)]
+pub struct TransformFusion {
+    shape: Vec<u64>,
+    ops: Vec<Op>,
+}
+
+impl TransformFusion {
+    /// Start a new fusion over a view with the given `shape`.
+    pub fn new(shape: Vec<u64>) -> TransformFusion {
+        TransformFusion {
+            shape,
+            ops: Vec::new(),
+        }
+    }
+
+    /// The shape this fusion would produce if applied now.
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    pub fn expand_dims(mut self, axis: usize) -> TransformFusion {
+        self.shape.insert(axis, 1);
+        self.ops.push(Op::Add(axis));
+        self.canonicalize();
+        self
+    }
+
+    pub fn squeeze(mut self, axis: usize) -> TransformFusion {
+        self.shape.remove(axis);
+        self.ops.push(Op::Rm(axis));
+        self.canonicalize();
+        self
+    }
+
+    pub fn transpose(mut self, permutation: Vec<usize>) -> TransformFusion {
+        let shape = permutation.iter().map(|&axis| self.shape[axis]).collect();
+        self.shape = shape;
+        self.ops.push(Op::Move(permutation));
+        self.canonicalize();
+        self
+    }
+
+    pub fn reshape(mut self, new_dims: Vec<u64>) -> TransformFusion {
+        self.ops
+            .push(Op::Reshape(self.shape.clone(), new_dims.clone()));
+        self.shape = new_dims;
+        self.canonicalize();
+        self
+    }
+
+    fn canonicalize(&mut self) {
+        loop {
+            let before = self.ops.clone();
+            self.ops = canonicalize_pass(std::mem::take(&mut self.ops));
+            if self.ops == before {
+                break;
+            }
+        }
+    }
+
+    /// Lower this fusion to one `transpose`, `expand_dims` or `reshape`
+    /// call over `view` per op in the (already canonical) op sequence,
+    /// applied in order. A standalone `Rm` returns a not-implemented error.
+    pub fn apply(&self, view: &TensorView) -> TCResult<TensorView> {
+        let mut view = view.clone();
+
+        for op in &self.ops {
+            view = match op {
+                Op::Move(permutation) => view.transpose(Some(permutation.clone()))?,
+                Op::Add(axis) => view.expand_dims(*axis)?,
+                Op::Reshape(_, new_dims) => view.reshape(Shape::from(new_dims.clone()))?,
+                Op::Rm(_) => {
+                    return Err(error::not_implemented(
+                        "TransformFusion::apply for a standalone Rm -- TensorTransform has no \
+                         axis-removal primitive in this checkout to lower it to",
+                    ));
+                }
+            };
+        }
+
+        Ok(view)
+    }
+}
+
+/// One pass of peephole rewrites over `ops`, applying the first
+/// applicable rule found at each adjacent pair (outer `canonicalize`
+/// calls this to a fixed point):
+///
+/// 1. Two adjacent `Move`s compose into one (by function composition of
+///    their permutations).
+/// 2. An `Add(i)` immediately followed by `Rm(i)` cancels.
+/// 3. A `Reshape` whose input and output dims are equal becomes a no-op.
+/// 4. An `Add`/`Rm` immediately followed by a `Move` can be swapped
+///    (pushing the `Move` earlier, so it has a chance to reach and merge
+///    with an earlier `Move` across rule 1) by adjusting indices.
+fn canonicalize_pass(ops: Vec<Op>) -> Vec<Op> {
+    let mut out: Vec<Op> = Vec::with_capacity(ops.len());
+
+    for op in ops {
+        match (out.last(), &op) {
+            (Some(Op::Move(prev)), Op::Move(next)) => {
+                let composed = next.iter().map(|&axis| prev[axis]).collect();
+                out.pop();
+                out.push(Op::Move(composed));
+            }
+            (Some(Op::Add(i)), Op::Rm(j)) if i == j => {
+                out.pop();
+            }
+            (_, Op::Reshape(old_dims, new_dims)) if old_dims == new_dims => {}
+            (Some(Op::Add(i)), Op::Move(permutation)) => {
+                let i = *i;
+                let (rest, moved_to) = push_past_add(permutation, i);
+                out.pop();
+                out.push(Op::Move(rest));
+                out.push(Op::Add(moved_to));
+            }
+            (Some(Op::Rm(i)), Op::Move(permutation)) => {
+                let i = *i;
+                let rest = push_past_rm(permutation, i);
+                let rm_at = rest.len() - 1;
+                out.pop();
+                out.push(Op::Move(rest));
+                out.push(Op::Rm(rm_at));
+            }
+            _ => out.push(op),
+        }
+    }
+
+    out
+}
+
+/// Swap `[Add(i), Move(permutation)]` to `[Move(rest), Add(moved_to)]`:
+/// `permutation` (length `rest.len() + 1`) operates on the array just
+/// after `Add(i)` inserted a new axis at `i`; `rest` is the equivalent
+/// permutation of the real axes alone (with `Add(i)`'s axis removed and
+/// indices shifted back down), and `moved_to` is the output position the
+/// new axis ends up at, which is where `Add` must now insert it.
+fn push_past_add(permutation: &[usize], i: usize) -> (Vec<usize>, usize) {
+    let moved_to = permutation
+        .iter()
+        .position(|&axis| axis == i)
+        .expect("Add's axis must appear exactly once in the following Move");
+
+    let rest = permutation
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| *k != moved_to)
+        .map(|(_, &axis)| if axis > i { axis - 1 } else { axis })
+        .collect();
+
+    (rest, moved_to)
+}
+
+/// Swap `[Rm(i), Move(permutation)]` to `[Move(rest), Rm(rest.len() - 1)]`:
+/// `permutation` (length `rest.len()`) operates on the array just after
+/// `Rm(i)` removed a real axis; `rest` is the equivalent permutation of
+/// all the original axes (with the removed one appended at the end, to
+/// be dropped again by the trailing `Rm`).
+fn push_past_rm(permutation: &[usize], i: usize) -> Vec<usize> {
+    let mut rest: Vec<usize> = permutation
+        .iter()
+        .map(|&axis| if axis >= i { axis + 1 } else { axis })
+        .collect();
+    rest.push(i);
+    rest
+}
@@ -1,5 +1,8 @@
 use std::convert::Infallible;
+use std::error::Error as StdError;
 use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub enum Code {
@@ -27,10 +30,41 @@ pub enum Code {
     // "The request payload itself is dangerously large"
     RequestTooLarge,
 
+    // "This request took too long to fulfill and has been abandoned"
+    Timeout,
+
+    // "You're sending requests faster than I can handle them--slow down"
+    TooManyRequests,
+
+    // "This resource can't be reached right now, but the fault is likely transient"
+    Unavailable,
+
     // "This resource requires authorization but your credentials are absent or nonsensical"
     Unauthorized,
 }
 
+impl Code {
+    /// The HTTP status code that corresponds to this `Code`, so the gateway
+    /// layer has one canonical mapping instead of matching on `Code` itself
+    /// at every call site.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Code::BadRequest => 400,
+            Code::Unauthorized => 401,
+            Code::Forbidden => 403,
+            Code::NotFound => 404,
+            Code::MethodNotAllowed => 405,
+            Code::Timeout => 408,
+            Code::Conflict => 409,
+            Code::RequestTooLarge => 413,
+            Code::TooManyRequests => 429,
+            Code::Internal => 500,
+            Code::NotImplemented => 501,
+            Code::Unavailable => 503,
+        }
+    }
+}
+
 impl fmt::Display for Code {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -42,6 +76,9 @@ impl fmt::Display for Code {
             Code::NotFound => write!(f, "Not found"),
             Code::NotImplemented => write!(f, "Not implemented"),
             Code::RequestTooLarge => write!(f, "Request too large"),
+            Code::Timeout => write!(f, "Request timed out"),
+            Code::TooManyRequests => write!(f, "Too many requests"),
+            Code::Unavailable => write!(f, "Service unavailable"),
             Code::Unauthorized => write!(f, "Unauthorized"),
         }
     }
@@ -51,11 +88,36 @@ impl fmt::Display for Code {
 pub struct TCError {
     reason: Code,
     message: String,
+    source: Option<Arc<dyn StdError + Send + Sync>>,
+    location: &'static Location<'static>,
 }
 
 impl TCError {
+    #[track_caller]
     pub fn of(reason: Code, message: String) -> TCError {
-        TCError { reason, message }
+        TCError {
+            reason,
+            message,
+            source: None,
+            location: Location::caller(),
+        }
+    }
+
+    /// Like [`TCError::of`], but keeps `cause` around so
+    /// `std::error::Error::source` (and the `{:#}` alternate `Display`
+    /// rendering) can walk back to it instead of discarding it.
+    #[track_caller]
+    pub fn with_source<E: StdError + Send + Sync + 'static>(
+        reason: Code,
+        message: String,
+        cause: E,
+    ) -> TCError {
+        TCError {
+            reason,
+            message,
+            source: Some(Arc::new(cause)),
+            location: Location::caller(),
+        }
     }
 
     pub fn message(&self) -> &str {
@@ -65,11 +127,16 @@ impl TCError {
     pub fn reason(&self) -> &Code {
         &self.reason
     }
+
+    /// Where this error was constructed, captured via `#[track_caller]`.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
 }
 
 impl From<Box<bincode::ErrorKind>> for TCError {
     fn from(e: Box<bincode::ErrorKind>) -> TCError {
-        bad_request("Serialization error", e)
+        TCError::with_source(Code::BadRequest, "Serialization error".to_string(), *e)
     }
 }
 
@@ -81,27 +148,46 @@ impl From<Infallible> for TCError {
 
 impl From<hyper::Error> for TCError {
     fn from(e: hyper::Error) -> TCError {
-        internal(format!("HTTP interface error: {}", e))
+        TCError::with_source(Code::Internal, "HTTP interface error".to_string(), e)
     }
 }
 
 impl From<serde_json::error::Error> for TCError {
     fn from(e: serde_json::error::Error) -> TCError {
-        bad_request("Serialization error", e)
+        TCError::with_source(Code::BadRequest, "Serialization error".to_string(), e)
     }
 }
 
-impl std::error::Error for TCError {}
+impl std::error::Error for TCError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_ref().map(|cause| cause.as_ref() as &(dyn StdError + 'static))
+    }
+}
 
 impl fmt::Debug for TCError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self)
+        write!(f, "{:#}", self)
     }
 }
 
 impl fmt::Display for TCError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}", self.reason, self.message)
+        write!(f, "{}: {}", self.reason, self.message)?;
+
+        // the `{:#}` alternate form additionally renders the full
+        // `std::error::Error::source` chain, one cause per line, instead of
+        // silently dropping everything but this error's own message
+        if f.alternate() {
+            write!(f, " ({})", self.location)?;
+
+            let mut cause = StdError::source(self);
+            while let Some(source) = cause {
+                write!(f, "\ncaused by: {}", source)?;
+                cause = source.source();
+            }
+        }
+
+        Ok(())
     }
 }
 
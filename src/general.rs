@@ -4,12 +4,20 @@ use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 
+use async_trait::async_trait;
+use futures::future;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use futures::Future;
 use futures::Stream;
 
 use crate::error;
 use crate::scalar::Id;
 
+/// The default number of in-flight futures for a [`Tuple`]/[`Map`] stream
+/// combinator, when a caller doesn't need to tune it with the `_with_buffer`
+/// variant.
+const DEFAULT_BUFFER: usize = 16;
+
 pub type TCBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a + Send>>;
 pub type TCBoxTryFuture<'a, T> = TCBoxFuture<'a, TCResult<T>>;
 pub type TCResult<T> = Result<T, error::TCError>;
@@ -306,6 +314,293 @@ impl<F: Clone, T1: TryCastFrom<F>, T2: TryCastFrom<F>, T3: TryCastFrom<F>, T4: T
     }
 }
 
+impl<
+        F: Clone,
+        T1: TryCastFrom<F>,
+        T2: TryCastFrom<F>,
+        T3: TryCastFrom<F>,
+        T4: TryCastFrom<F>,
+        T5: TryCastFrom<F>,
+    > TryCastFrom<Tuple<F>> for (T1, T2, T3, T4, T5)
+{
+    fn can_cast_from(source: &Tuple<F>) -> bool {
+        source.len() == 5
+            && T1::can_cast_from(&source[0])
+            && T2::can_cast_from(&source[1])
+            && T3::can_cast_from(&source[2])
+            && T4::can_cast_from(&source[3])
+            && T5::can_cast_from(&source[4])
+    }
+
+    fn opt_cast_from(mut source: Tuple<F>) -> Option<(T1, T2, T3, T4, T5)> {
+        if source.len() == 5 {
+            let fifth: Option<T5> = source.pop().unwrap().opt_cast_into();
+            let fourth: Option<T4> = source.pop().unwrap().opt_cast_into();
+            let third: Option<T3> = source.pop().unwrap().opt_cast_into();
+            let second: Option<T2> = source.pop().unwrap().opt_cast_into();
+            let first: Option<T1> = source.pop().unwrap().opt_cast_into();
+            match (first, second, third, fourth, fifth) {
+                (Some(first), Some(second), Some(third), Some(fourth), Some(fifth)) => {
+                    Some((first, second, third, fourth, fifth))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<
+        F: Clone,
+        T1: TryCastFrom<F>,
+        T2: TryCastFrom<F>,
+        T3: TryCastFrom<F>,
+        T4: TryCastFrom<F>,
+        T5: TryCastFrom<F>,
+        T6: TryCastFrom<F>,
+    > TryCastFrom<Tuple<F>> for (T1, T2, T3, T4, T5, T6)
+{
+    fn can_cast_from(source: &Tuple<F>) -> bool {
+        source.len() == 6
+            && T1::can_cast_from(&source[0])
+            && T2::can_cast_from(&source[1])
+            && T3::can_cast_from(&source[2])
+            && T4::can_cast_from(&source[3])
+            && T5::can_cast_from(&source[4])
+            && T6::can_cast_from(&source[5])
+    }
+
+    fn opt_cast_from(mut source: Tuple<F>) -> Option<(T1, T2, T3, T4, T5, T6)> {
+        if source.len() == 6 {
+            let sixth: Option<T6> = source.pop().unwrap().opt_cast_into();
+            let fifth: Option<T5> = source.pop().unwrap().opt_cast_into();
+            let fourth: Option<T4> = source.pop().unwrap().opt_cast_into();
+            let third: Option<T3> = source.pop().unwrap().opt_cast_into();
+            let second: Option<T2> = source.pop().unwrap().opt_cast_into();
+            let first: Option<T1> = source.pop().unwrap().opt_cast_into();
+            match (first, second, third, fourth, fifth, sixth) {
+                (Some(first), Some(second), Some(third), Some(fourth), Some(fifth), Some(sixth)) => {
+                    Some((first, second, third, fourth, fifth, sixth))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<
+        F: Clone,
+        T1: TryCastFrom<F>,
+        T2: TryCastFrom<F>,
+        T3: TryCastFrom<F>,
+        T4: TryCastFrom<F>,
+        T5: TryCastFrom<F>,
+        T6: TryCastFrom<F>,
+        T7: TryCastFrom<F>,
+    > TryCastFrom<Tuple<F>> for (T1, T2, T3, T4, T5, T6, T7)
+{
+    fn can_cast_from(source: &Tuple<F>) -> bool {
+        source.len() == 7
+            && T1::can_cast_from(&source[0])
+            && T2::can_cast_from(&source[1])
+            && T3::can_cast_from(&source[2])
+            && T4::can_cast_from(&source[3])
+            && T5::can_cast_from(&source[4])
+            && T6::can_cast_from(&source[5])
+            && T7::can_cast_from(&source[6])
+    }
+
+    fn opt_cast_from(mut source: Tuple<F>) -> Option<(T1, T2, T3, T4, T5, T6, T7)> {
+        if source.len() == 7 {
+            let seventh: Option<T7> = source.pop().unwrap().opt_cast_into();
+            let sixth: Option<T6> = source.pop().unwrap().opt_cast_into();
+            let fifth: Option<T5> = source.pop().unwrap().opt_cast_into();
+            let fourth: Option<T4> = source.pop().unwrap().opt_cast_into();
+            let third: Option<T3> = source.pop().unwrap().opt_cast_into();
+            let second: Option<T2> = source.pop().unwrap().opt_cast_into();
+            let first: Option<T1> = source.pop().unwrap().opt_cast_into();
+            match (first, second, third, fourth, fifth, sixth, seventh) {
+                (
+                    Some(first),
+                    Some(second),
+                    Some(third),
+                    Some(fourth),
+                    Some(fifth),
+                    Some(sixth),
+                    Some(seventh),
+                ) => Some((first, second, third, fourth, fifth, sixth, seventh)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<
+        F: Clone,
+        T1: TryCastFrom<F>,
+        T2: TryCastFrom<F>,
+        T3: TryCastFrom<F>,
+        T4: TryCastFrom<F>,
+        T5: TryCastFrom<F>,
+        T6: TryCastFrom<F>,
+        T7: TryCastFrom<F>,
+        T8: TryCastFrom<F>,
+    > TryCastFrom<Tuple<F>> for (T1, T2, T3, T4, T5, T6, T7, T8)
+{
+    fn can_cast_from(source: &Tuple<F>) -> bool {
+        source.len() == 8
+            && T1::can_cast_from(&source[0])
+            && T2::can_cast_from(&source[1])
+            && T3::can_cast_from(&source[2])
+            && T4::can_cast_from(&source[3])
+            && T5::can_cast_from(&source[4])
+            && T6::can_cast_from(&source[5])
+            && T7::can_cast_from(&source[6])
+            && T8::can_cast_from(&source[7])
+    }
+
+    fn opt_cast_from(mut source: Tuple<F>) -> Option<(T1, T2, T3, T4, T5, T6, T7, T8)> {
+        if source.len() == 8 {
+            let eighth: Option<T8> = source.pop().unwrap().opt_cast_into();
+            let seventh: Option<T7> = source.pop().unwrap().opt_cast_into();
+            let sixth: Option<T6> = source.pop().unwrap().opt_cast_into();
+            let fifth: Option<T5> = source.pop().unwrap().opt_cast_into();
+            let fourth: Option<T4> = source.pop().unwrap().opt_cast_into();
+            let third: Option<T3> = source.pop().unwrap().opt_cast_into();
+            let second: Option<T2> = source.pop().unwrap().opt_cast_into();
+            let first: Option<T1> = source.pop().unwrap().opt_cast_into();
+            match (first, second, third, fourth, fifth, sixth, seventh, eighth) {
+                (
+                    Some(first),
+                    Some(second),
+                    Some(third),
+                    Some(fourth),
+                    Some(fifth),
+                    Some(sixth),
+                    Some(seventh),
+                    Some(eighth),
+                ) => Some((first, second, third, fourth, fifth, sixth, seventh, eighth)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// A blanket cast from a [`Tuple`] of 2-tuples into a [`Map`]: useful when a
+/// caller has a list of `(Id, value)` pairs (e.g. parsed positionally) and
+/// wants to work with it as a keyed [`Map`] instead.
+impl<F: Clone, T: TryCastFrom<F>> TryCastFrom<Tuple<F>> for Map<T>
+where
+    (Id, T): TryCastFrom<F>,
+{
+    fn can_cast_from(tuple: &Tuple<F>) -> bool {
+        tuple.iter().all(<(Id, T)>::can_cast_from)
+    }
+
+    fn opt_cast_from(tuple: Tuple<F>) -> Option<Self> {
+        tuple
+            .into_inner()
+            .into_iter()
+            .map(|item| item.opt_cast_into())
+            .collect()
+    }
+}
+
+/// A fluent surface over [`TryCastFrom`]: `matches::<T>()` checks whether a
+/// cast would succeed without consuming `self`.
+pub trait Match {
+    fn matches<T: TryCastFrom<Self>>(&self) -> bool
+    where
+        Self: Sized;
+}
+
+impl<F> Match for F {
+    fn matches<T: TryCastFrom<F>>(&self) -> bool {
+        T::can_cast_from(self)
+    }
+}
+
+/// Destructure a [`Map`] into a fixed-arity tuple of named fields, by looking
+/// up `keys` (in declared order) and casting each value found. Returns `None`
+/// if a key is missing or any value fails `can_cast_from`, the same failure
+/// mode as the rest of the `TryCastFrom` ladder.
+pub trait CastFields<F: Clone>: Sized {
+    fn opt_cast_fields(map: Map<F>, keys: &[Id]) -> Option<Self>;
+}
+
+impl<T: Clone> Map<T> {
+    pub fn opt_cast_into_fields<Spec: CastFields<T>>(self, keys: &[Id]) -> Option<Spec> {
+        Spec::opt_cast_fields(self, keys)
+    }
+}
+
+fn take_field<F: Clone, T: TryCastFrom<F>>(map: &mut Map<F>, key: &Id) -> Option<T> {
+    map.inner.remove(key)?.opt_cast_into()
+}
+
+impl<F: Clone, T1: TryCastFrom<F>> CastFields<F> for (T1,) {
+    fn opt_cast_fields(mut map: Map<F>, keys: &[Id]) -> Option<Self> {
+        if keys.len() != 1 {
+            return None;
+        }
+
+        Some((take_field(&mut map, &keys[0])?,))
+    }
+}
+
+impl<F: Clone, T1: TryCastFrom<F>, T2: TryCastFrom<F>> CastFields<F> for (T1, T2) {
+    fn opt_cast_fields(mut map: Map<F>, keys: &[Id]) -> Option<Self> {
+        if keys.len() != 2 {
+            return None;
+        }
+
+        Some((
+            take_field(&mut map, &keys[0])?,
+            take_field(&mut map, &keys[1])?,
+        ))
+    }
+}
+
+impl<F: Clone, T1: TryCastFrom<F>, T2: TryCastFrom<F>, T3: TryCastFrom<F>> CastFields<F>
+    for (T1, T2, T3)
+{
+    fn opt_cast_fields(mut map: Map<F>, keys: &[Id]) -> Option<Self> {
+        if keys.len() != 3 {
+            return None;
+        }
+
+        Some((
+            take_field(&mut map, &keys[0])?,
+            take_field(&mut map, &keys[1])?,
+            take_field(&mut map, &keys[2])?,
+        ))
+    }
+}
+
+impl<F: Clone, T1: TryCastFrom<F>, T2: TryCastFrom<F>, T3: TryCastFrom<F>, T4: TryCastFrom<F>>
+    CastFields<F> for (T1, T2, T3, T4)
+{
+    fn opt_cast_fields(mut map: Map<F>, keys: &[Id]) -> Option<Self> {
+        if keys.len() != 4 {
+            return None;
+        }
+
+        Some((
+            take_field(&mut map, &keys[0])?,
+            take_field(&mut map, &keys[1])?,
+            take_field(&mut map, &keys[2])?,
+            take_field(&mut map, &keys[3])?,
+        ))
+    }
+}
+
 impl<T: Clone + fmt::Display> fmt::Display for Tuple<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -319,3 +614,256 @@ impl<T: Clone + fmt::Display> fmt::Display for Tuple<T> {
         )
     }
 }
+
+/// A source of encoded elements for a [`FromStream`] decode, modeled on the
+/// `destream` crate's `Decoder`/sequence-and-map access pattern (kept local,
+/// rather than depending on the real `destream` crate, since this snapshot of
+/// the crate has no Cargo manifest to add it to). Elements are read one at a
+/// time rather than all at once, so a decoder backed by an actual incoming
+/// byte stream never has to buffer the whole sequence or map in memory.
+#[async_trait]
+pub trait Decoder: Send {
+    /// Decode the next element of a sequence being read, or `None` once the
+    /// sequence is exhausted.
+    async fn decode_seq_next<T: FromStream>(&mut self) -> TCResult<Option<T>>;
+
+    /// Decode the next `(Id, value)` entry of a map being read, or `None`
+    /// once the map is exhausted.
+    async fn decode_map_next<T: FromStream>(&mut self) -> TCResult<Option<(Id, T)>>;
+}
+
+/// The `destream`-style counterpart of [`Decoder`]: a destination that a
+/// [`ToStream`] implementation pushes its elements into one at a time.
+#[async_trait]
+pub trait Encoder: Send {
+    async fn encode_seq_item<T: ToStream + Sync>(&mut self, item: &T) -> TCResult<()>;
+
+    async fn encode_map_item<T: ToStream + Sync>(&mut self, key: &Id, item: &T) -> TCResult<()>;
+}
+
+/// A type that can be decoded incrementally from a [`Decoder`].
+#[async_trait]
+pub trait FromStream: Sized + Send {
+    async fn from_stream<D: Decoder>(decoder: &mut D) -> TCResult<Self>;
+}
+
+/// A type that can be encoded incrementally into an [`Encoder`].
+#[async_trait]
+pub trait ToStream: Send + Sync {
+    async fn to_stream<E: Encoder>(&self, encoder: &mut E) -> TCResult<()>;
+}
+
+#[async_trait]
+impl<T: FromStream> FromStream for Tuple<T> {
+    async fn from_stream<D: Decoder>(decoder: &mut D) -> TCResult<Self> {
+        let mut inner = Vec::new();
+
+        while let Some(item) = decoder.decode_seq_next().await? {
+            inner.push(item);
+        }
+
+        Ok(inner.into())
+    }
+}
+
+#[async_trait]
+impl<T: ToStream + Clone> ToStream for Tuple<T> {
+    async fn to_stream<E: Encoder>(&self, encoder: &mut E) -> TCResult<()> {
+        for item in self.inner.iter() {
+            encoder.encode_seq_item(item).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: FromStream + Clone> FromStream for Map<T> {
+    async fn from_stream<D: Decoder>(decoder: &mut D) -> TCResult<Self> {
+        let mut inner = HashMap::new();
+
+        while let Some((id, value)) = decoder.decode_map_next().await? {
+            inner.insert(id, value);
+        }
+
+        Ok(inner.into())
+    }
+}
+
+#[async_trait]
+impl<T: ToStream + Clone> ToStream for Map<T> {
+    async fn to_stream<E: Encoder>(&self, encoder: &mut E) -> TCResult<()> {
+        for (key, value) in self.inner.iter() {
+            encoder.encode_map_item(key, value).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Clone + Send + 'static> Tuple<T> {
+    /// Apply `f` to each element with up to [`DEFAULT_BUFFER`] futures in
+    /// flight at once, yielding results as they resolve rather than in order.
+    pub fn map<U, F>(self, f: F) -> TCTryStream<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        self.map_with_buffer(f, DEFAULT_BUFFER)
+    }
+
+    /// Like [`Tuple::map`], but with a caller-chosen concurrency limit.
+    pub fn map_with_buffer<U, F>(self, f: F, buffer: usize) -> TCTryStream<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        let stream = stream::iter(self.inner.into_iter())
+            .map(f)
+            .buffer_unordered(buffer);
+
+        Box::pin(stream)
+    }
+
+    /// Like [`Tuple::map`], but stop polling for further elements as soon as
+    /// one of them resolves to an `Err`.
+    pub fn try_map<U, F>(self, f: F) -> TCTryStream<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        let stream = self.map_with_buffer(f, DEFAULT_BUFFER);
+
+        let stream = stream.scan(false, |failed, result| {
+            if *failed {
+                return future::ready(None);
+            }
+
+            if result.is_err() {
+                *failed = true;
+            }
+
+            future::ready(Some(result))
+        });
+
+        Box::pin(stream)
+    }
+
+    /// Keep only the elements for which the async predicate `f` resolves to
+    /// `true`, running up to [`DEFAULT_BUFFER`] evaluations concurrently.
+    pub fn filter<F>(self, f: F) -> TCTryStream<T>
+    where
+        F: Fn(&T) -> TCBoxTryFuture<'static, bool> + Send + 'static,
+    {
+        let stream = stream::iter(self.inner.into_iter())
+            .map(move |item| {
+                let keep = f(&item);
+                async move { keep.await.map(|keep| (keep, item)) }
+            })
+            .buffer_unordered(DEFAULT_BUFFER)
+            .try_filter_map(|(keep, item)| future::ok(if keep { Some(item) } else { None }));
+
+        Box::pin(stream)
+    }
+
+    /// Fold the tuple's elements into a single value, applying `f` in order
+    /// and short-circuiting on the first `Err`.
+    pub async fn reduce<Acc, F>(self, init: Acc, f: F) -> TCResult<Acc>
+    where
+        F: Fn(Acc, T) -> TCBoxTryFuture<'static, Acc>,
+    {
+        let mut acc = init;
+
+        for item in self.inner.into_iter() {
+            acc = f(acc, item).await?;
+        }
+
+        Ok(acc)
+    }
+}
+
+impl<T: Clone + Send + 'static> Map<T> {
+    /// Apply `f` to each `(Id, value)` entry with up to [`DEFAULT_BUFFER`]
+    /// futures in flight at once, yielding `(Id, U)` results as they resolve.
+    pub fn map<U, F>(self, f: F) -> TCTryStream<(Id, U)>
+    where
+        U: Send + 'static,
+        F: Fn(Id, T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        self.map_with_buffer(f, DEFAULT_BUFFER)
+    }
+
+    /// Like [`Map::map`], but with a caller-chosen concurrency limit.
+    pub fn map_with_buffer<U, F>(self, f: F, buffer: usize) -> TCTryStream<(Id, U)>
+    where
+        U: Send + 'static,
+        F: Fn(Id, T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        let stream = stream::iter(self.inner.into_iter())
+            .map(move |(id, value)| {
+                let result = f(id.clone(), value);
+                async move { result.await.map(|value| (id, value)) }
+            })
+            .buffer_unordered(buffer);
+
+        Box::pin(stream)
+    }
+
+    /// Like [`Map::map`], but stop polling for further entries as soon as one
+    /// of them resolves to an `Err`.
+    pub fn try_map<U, F>(self, f: F) -> TCTryStream<(Id, U)>
+    where
+        U: Send + 'static,
+        F: Fn(Id, T) -> TCBoxTryFuture<'static, U> + Send + 'static,
+    {
+        let stream = self.map_with_buffer(f, DEFAULT_BUFFER);
+
+        let stream = stream.scan(false, |failed, result| {
+            if *failed {
+                return future::ready(None);
+            }
+
+            if result.is_err() {
+                *failed = true;
+            }
+
+            future::ready(Some(result))
+        });
+
+        Box::pin(stream)
+    }
+
+    /// Keep only the entries for which the async predicate `f` resolves to
+    /// `true`, running up to [`DEFAULT_BUFFER`] evaluations concurrently.
+    pub fn filter<F>(self, f: F) -> TCTryStream<(Id, T)>
+    where
+        F: Fn(&Id, &T) -> TCBoxTryFuture<'static, bool> + Send + 'static,
+    {
+        let stream = stream::iter(self.inner.into_iter())
+            .map(move |(id, value)| {
+                let keep = f(&id, &value);
+                async move { keep.await.map(|keep| (keep, id, value)) }
+            })
+            .buffer_unordered(DEFAULT_BUFFER)
+            .try_filter_map(|(keep, id, value)| {
+                future::ok(if keep { Some((id, value)) } else { None })
+            });
+
+        Box::pin(stream)
+    }
+
+    /// Fold the map's entries into a single value, applying `f` in arbitrary
+    /// (hash map iteration) order and short-circuiting on the first `Err`.
+    pub async fn reduce<Acc, F>(self, init: Acc, f: F) -> TCResult<Acc>
+    where
+        F: Fn(Acc, (Id, T)) -> TCBoxTryFuture<'static, Acc>,
+    {
+        let mut acc = init;
+
+        for entry in self.inner.into_iter() {
+            acc = f(acc, entry).await?;
+        }
+
+        Ok(acc)
+    }
+}
@@ -0,0 +1,111 @@
+//! A write-ahead log for `Txn::commit`'s two-phase fan-out, so a crash
+//! mid-commit leaves a durable record of intent instead of silently mixing
+//! committed and uncommitted participants. [`MemoryTxnLog`] is the
+//! in-process stand-in for a `Dir`-backed log; it recovers a `Txn` that
+//! panicked partway through a commit, not one a process restart interrupted.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use crate::error;
+use crate::transaction::{Transact, TxnId};
+use crate::value::TCResult;
+
+/// Whether a logged `TxnId` has been fully driven through `Transact::commit`
+/// yet.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum TxnLogState {
+    Prepared,
+    Committed,
+}
+
+/// A durable record of which `Transact` participants a `Txn` was about to
+/// fan `commit`/`rollback` out to, so a startup scan can finish the job for
+/// any `txn_id` left in `Prepared` state.
+#[async_trait]
+pub trait TxnLog: Send + Sync {
+    /// Record that `subjects` are about to be driven through
+    /// `Transact::prepare` and then `Transact::commit` for `txn_id`. Written
+    /// before either fan-out begins.
+    async fn prepare(&self, txn_id: &TxnId, subjects: Vec<Arc<dyn Transact>>) -> TCResult<()>;
+
+    /// Record that every participant logged under `txn_id` committed
+    /// successfully, so a startup scan no longer needs to replay it.
+    async fn commit(&self, txn_id: &TxnId) -> TCResult<()>;
+
+    /// Drop `txn_id`'s record outright, e.g. once its participants have
+    /// been rolled back.
+    async fn rollback(&self, txn_id: &TxnId) -> TCResult<()>;
+
+    /// Every `TxnId` still in `Prepared` state, with the participants
+    /// logged under it -- left behind by a commit that never reached
+    /// `TxnLog::commit`, for a startup scan to replay `rollback` (or
+    /// re-drive `commit`) against.
+    async fn dangling(&self) -> TCResult<Vec<(TxnId, Vec<Arc<dyn Transact>>)>>;
+}
+
+/// An in-process [`TxnLog`]: durable across a panic unwound elsewhere in the
+/// same process, not across a process restart.
+#[derive(Default)]
+pub struct MemoryTxnLog {
+    records: RwLock<HashMap<TxnId, (TxnLogState, Vec<Arc<dyn Transact>>)>>,
+}
+
+#[async_trait]
+impl TxnLog for MemoryTxnLog {
+    async fn prepare(&self, txn_id: &TxnId, subjects: Vec<Arc<dyn Transact>>) -> TCResult<()> {
+        let mut records = self.records.write().map_err(|_| error::internal("TxnLog lock poisoned"))?;
+        records.insert(txn_id.clone(), (TxnLogState::Prepared, subjects));
+        Ok(())
+    }
+
+    async fn commit(&self, txn_id: &TxnId) -> TCResult<()> {
+        let mut records = self.records.write().map_err(|_| error::internal("TxnLog lock poisoned"))?;
+        if let Some(record) = records.get_mut(txn_id) {
+            record.0 = TxnLogState::Committed;
+        }
+
+        Ok(())
+    }
+
+    async fn rollback(&self, txn_id: &TxnId) -> TCResult<()> {
+        let mut records = self.records.write().map_err(|_| error::internal("TxnLog lock poisoned"))?;
+        records.remove(txn_id);
+        Ok(())
+    }
+
+    async fn dangling(&self) -> TCResult<Vec<(TxnId, Vec<Arc<dyn Transact>>)>> {
+        let records = self.records.read().map_err(|_| error::internal("TxnLog lock poisoned"))?;
+        Ok(records
+            .iter()
+            .filter(|(_, (state, _))| *state == TxnLogState::Prepared)
+            .map(|(txn_id, (_, subjects))| (txn_id.clone(), subjects.clone()))
+            .collect())
+    }
+}
+
+/// Convenience constructor for the default log, as an `Arc<dyn TxnLog>`
+/// ready to hand to `Txn::new` the way `repo::default_repo` hands out the
+/// default [`crate::internal::Repo`].
+pub fn default_log() -> Arc<dyn TxnLog> {
+    Arc::new(MemoryTxnLog::default())
+}
+
+/// Replay every dangling `Prepared` record in `log`: re-drive `rollback`
+/// for each of its participants, since a commit that never reached
+/// `TxnLog::commit` can't be assumed to have finished fanning out. A real
+/// `Host`/`Dir` would call this once at startup, before accepting new
+/// transactions.
+pub async fn recover(log: &dyn TxnLog) -> TCResult<()> {
+    for (txn_id, subjects) in log.dangling().await? {
+        for subject in &subjects {
+            subject.rollback(&txn_id).await;
+        }
+
+        log.rollback(&txn_id).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! A pluggable sink for transaction-subsystem metrics, replacing the
+//! `println!("commit!")`/`println!("ready: {}", ...)`/`println!("{}
+//! remaining to resolve", ...)` calls in `transaction.rs` that were
+//! previously the only visibility into `Txn` behavior.
+//!
+//! [`TxnMetrics`] is the seam `Txn`/`TxnState` record through instead of
+//! printing directly; `Txn::new` takes its sink from `host.metrics()`, the
+//! same way it takes its `TxnLog` from `host.log()`.
+//! [`NoopTxnMetrics`] is the default -- recording costs nothing until an
+//! operator opts into [`MemoryTxnMetrics`], whose [`TxnMetrics::snapshot`]
+//! returns everything recorded.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// A counter, gauge, or per-op latency histogram a `Txn` records against.
+pub trait TxnMetrics: Send + Sync {
+    /// Increment a monotonic counter by one, e.g. `Txn::commit`/`rollback`
+    /// totals or resolve-loop iteration counts.
+    fn incr(&self, counter: &'static str);
+
+    /// Record the current value of a point-in-time gauge, e.g. the number
+    /// of ops still pending after a resolve-loop pass.
+    fn gauge(&self, name: &'static str, value: i64);
+
+    /// Record one latency observation for `histogram`, keyed by `op`
+    /// (`"Get"`/`"Put"`/`"Post"`), e.g. a single `Txn::resolve_value` call.
+    fn observe(&self, histogram: &'static str, op: &'static str, elapsed: Duration);
+
+    /// Everything recorded so far, for an operator to poll.
+    fn snapshot(&self) -> TxnMetricsSnapshot;
+}
+
+/// A point-in-time read of every counter, gauge, and histogram a
+/// [`TxnMetrics`] implementation has recorded.
+#[derive(Clone, Debug, Default)]
+pub struct TxnMetricsSnapshot {
+    pub counters: HashMap<String, u64>,
+    pub gauges: HashMap<String, i64>,
+    pub histograms: HashMap<String, HistogramSnapshot>,
+}
+
+/// The aggregate of a histogram's observations: how many there were, and
+/// their total duration, so a caller can derive a mean without this module
+/// committing to any particular set of percentiles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub total: Duration,
+}
+
+/// Discards everything recorded against it -- the default until an
+/// operator asks for visibility, so instrumentation costs nothing by
+/// default.
+#[derive(Default)]
+pub struct NoopTxnMetrics;
+
+impl TxnMetrics for NoopTxnMetrics {
+    fn incr(&self, _counter: &'static str) {}
+
+    fn gauge(&self, _name: &'static str, _value: i64) {}
+
+    fn observe(&self, _histogram: &'static str, _op: &'static str, _elapsed: Duration) {}
+
+    fn snapshot(&self) -> TxnMetricsSnapshot {
+        TxnMetricsSnapshot::default()
+    }
+}
+
+/// Aggregates every counter, gauge, and histogram observation into memory
+/// for the lifetime of the process, for a [`TxnMetrics::snapshot`] caller
+/// to poll.
+#[derive(Default)]
+pub struct MemoryTxnMetrics {
+    counters: RwLock<HashMap<&'static str, u64>>,
+    gauges: RwLock<HashMap<&'static str, i64>>,
+    histograms: RwLock<HashMap<(&'static str, &'static str), HistogramSnapshot>>,
+}
+
+impl TxnMetrics for MemoryTxnMetrics {
+    fn incr(&self, counter: &'static str) {
+        let mut counters = self.counters.write().expect("metrics counters");
+        *counters.entry(counter).or_insert(0) += 1;
+    }
+
+    fn gauge(&self, name: &'static str, value: i64) {
+        let mut gauges = self.gauges.write().expect("metrics gauges");
+        gauges.insert(name, value);
+    }
+
+    fn observe(&self, histogram: &'static str, op: &'static str, elapsed: Duration) {
+        let mut histograms = self.histograms.write().expect("metrics histograms");
+        let entry = histograms.entry((histogram, op)).or_default();
+        entry.count += 1;
+        entry.total += elapsed;
+    }
+
+    fn snapshot(&self) -> TxnMetricsSnapshot {
+        let counters = self
+            .counters
+            .read()
+            .expect("metrics counters")
+            .iter()
+            .map(|(name, count)| (name.to_string(), *count))
+            .collect();
+
+        let gauges = self
+            .gauges
+            .read()
+            .expect("metrics gauges")
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+
+        let histograms = self
+            .histograms
+            .read()
+            .expect("metrics histograms")
+            .iter()
+            .map(|((histogram, op), snapshot)| (format!("{}.{}", histogram, op), *snapshot))
+            .collect();
+
+        TxnMetricsSnapshot {
+            counters,
+            gauges,
+            histograms,
+        }
+    }
+}
+
+/// Convenience constructor for the default sink, as an `Arc<dyn
+/// TxnMetrics>` ready to hand to `Txn::new` via `Host::metrics`.
+pub fn default_metrics() -> Arc<dyn TxnMetrics> {
+    Arc::new(NoopTxnMetrics::default())
+}
@@ -2,6 +2,10 @@ pub mod chain;
 mod dir;
 pub mod file;
 mod history;
+pub mod log;
+pub mod metrics;
+pub mod mvcc;
+pub mod repo;
 mod store;
 
 pub const RECORD_DELIMITER: char = 30 as char;
@@ -10,3 +14,8 @@ pub const GROUP_DELIMITER: char = 29 as char;
 pub type Dir = dir::Dir;
 pub type History<O> = history::History<O>;
 pub type Store = store::Store;
+pub type Versioned<K, V> = mvcc::Versioned<K, V>;
+
+pub use log::TxnLog;
+pub use metrics::TxnMetrics;
+pub use repo::Repo;
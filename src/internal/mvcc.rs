@@ -0,0 +1,184 @@
+//! A reusable multi-version concurrency control layer, so that a
+//! `TCContext` implementation doesn't need to hand-roll its own per-key
+//! version history and write-conflict detection on top of `commit`.
+//!
+//! `crate::context::TCContext` has no definition anywhere in this tree, so
+//! nothing here wires a [`Versioned`] into an actual `TCContext` impl --
+//! this module only provides the `Versioned` store itself, ready for
+//! `crate::state::Graph` (or a future collection type) to delegate to once
+//! that foundation exists.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::RwLock;
+
+use crate::error;
+use crate::general::TCResult;
+use crate::transaction::TxnId;
+
+/// One key's committed version history, oldest first.
+struct History<V> {
+    versions: Vec<(TxnId, V)>,
+}
+
+impl<V: Clone> History<V> {
+    fn new() -> Self {
+        History {
+            versions: Vec::new(),
+        }
+    }
+
+    /// The newest version committed at or before `txn_id`, if any.
+    fn at(&self, txn_id: &TxnId) -> Option<&V> {
+        self.versions
+            .iter()
+            .rev()
+            .find(|(version_id, _)| version_id <= txn_id)
+            .map(|(_, value)| value)
+    }
+
+    /// The `TxnId` of the newest committed version, if any.
+    fn newest(&self) -> Option<&TxnId> {
+        self.versions.last().map(|(version_id, _)| version_id)
+    }
+
+    fn push(&mut self, txn_id: TxnId, value: V) {
+        self.versions.push((txn_id, value));
+    }
+
+    /// Drop every version older than the newest one still visible to
+    /// `min_active`, since no future reader can ever ask for them again.
+    fn gc(&mut self, min_active: &TxnId) {
+        let cutoff = self
+            .versions
+            .iter()
+            .rposition(|(version_id, _)| version_id <= min_active);
+
+        if let Some(cutoff) = cutoff {
+            self.versions.drain(..cutoff);
+        }
+    }
+}
+
+/// A multi-version store keyed on `K`, giving every reader a consistent
+/// snapshot as of its own [`TxnId`] without blocking concurrent writers, and
+/// detecting write conflicts between overlapping transactions at commit time.
+pub struct Versioned<K: Eq + Hash + Clone, V: Clone> {
+    committed: RwLock<HashMap<K, History<V>>>,
+    pending: RwLock<HashMap<TxnId, HashMap<K, V>>>,
+    read_sets: RwLock<HashMap<TxnId, HashSet<K>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Versioned<K, V> {
+    pub fn new() -> Self {
+        Versioned {
+            committed: RwLock::new(HashMap::new()),
+            pending: RwLock::new(HashMap::new()),
+            read_sets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The value visible to `txn_id`: its own pending write if present,
+    /// otherwise the newest version committed at or before `txn_id`.
+    pub fn get(&self, txn_id: &TxnId, key: &K) -> TCResult<Option<V>> {
+        if let Some(value) = self
+            .pending
+            .read()
+            .unwrap()
+            .get(txn_id)
+            .and_then(|writes| writes.get(key))
+        {
+            return Ok(Some(value.clone()));
+        }
+
+        self.read_sets
+            .write()
+            .unwrap()
+            .entry(txn_id.clone())
+            .or_insert_with(HashSet::new)
+            .insert(key.clone());
+
+        Ok(self
+            .committed
+            .read()
+            .unwrap()
+            .get(key)
+            .and_then(|history| history.at(txn_id))
+            .cloned())
+    }
+
+    /// Stage `value` under `txn_id`, to be promoted to a committed version by
+    /// [`Versioned::commit`].
+    pub fn put(&self, txn_id: &TxnId, key: K, value: V) -> TCResult<()> {
+        self.pending
+            .write()
+            .unwrap()
+            .entry(txn_id.clone())
+            .or_insert_with(HashMap::new)
+            .insert(key, value);
+
+        Ok(())
+    }
+
+    /// Promote `txn_id`'s pending writes to a new committed version, failing
+    /// with [`error::conflict`] (and leaving `txn_id`'s pending writes in
+    /// place for the caller to [`Versioned::rollback`]) if some other
+    /// transaction already committed a write to a key this transaction read
+    /// or wrote.
+    pub fn commit(&self, txn_id: &TxnId) -> TCResult<()> {
+        let mut touched = self.read_sets.write().unwrap().remove(txn_id).unwrap_or_default();
+        if let Some(writes) = self.pending.read().unwrap().get(txn_id) {
+            touched.extend(writes.keys().cloned());
+        }
+
+        {
+            let committed = self.committed.read().unwrap();
+            for key in &touched {
+                if let Some(newest) = committed.get(key).and_then(History::newest) {
+                    if newest > txn_id {
+                        return Err(error::conflict());
+                    }
+                }
+            }
+        }
+
+        if let Some(writes) = self.pending.write().unwrap().remove(txn_id) {
+            let mut committed = self.committed.write().unwrap();
+            for (key, value) in writes {
+                committed
+                    .entry(key)
+                    .or_insert_with(History::new)
+                    .push(txn_id.clone(), value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Discard `txn_id`'s pending writes and read set without committing
+    /// them.
+    pub fn rollback(&self, txn_id: &TxnId) {
+        self.pending.write().unwrap().remove(txn_id);
+        self.read_sets.write().unwrap().remove(txn_id);
+    }
+
+    /// Drop committed versions older than the oldest transaction still in
+    /// `active`, since no reader can ever ask for them again.
+    pub fn gc<'a>(&self, active: impl Iterator<Item = &'a TxnId>) {
+        let min_active = match active.min() {
+            Some(txn_id) => txn_id.clone(),
+            None => return,
+        };
+
+        let mut committed = self.committed.write().unwrap();
+        for history in committed.values_mut() {
+            history.gc(&min_active);
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for Versioned<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
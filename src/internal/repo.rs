@@ -0,0 +1,285 @@
+//! A pluggable persistence backend for [`crate::state::Persistent`]
+//! collections, reached via `Txn::repo` rather than a hard-wired concrete
+//! store, so a deployment can choose an implementation -- in-memory, local
+//! filesystem, or an external SQL store -- without changing `Collection`'s
+//! or `Persistent::create`'s own API.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use crate::error;
+use crate::transaction::TxnId;
+use crate::value::{TCResult, Value, ValueId};
+
+/// Durable storage for a collection's raw block data, keyed by `TxnId` so a
+/// backend can isolate each transaction's writes until commit.
+#[async_trait]
+pub trait BlockRepo: Send + Sync {
+    async fn read_block(&self, txn_id: &TxnId, block_id: &ValueId) -> TCResult<Vec<u8>>;
+
+    async fn write_block(&self, txn_id: &TxnId, block_id: ValueId, data: Vec<u8>) -> TCResult<()>;
+
+    async fn delete_block(&self, txn_id: &TxnId, block_id: &ValueId) -> TCResult<()>;
+}
+
+/// Durable storage for a collection's own schema/configuration, as opposed
+/// to the rows or blocks it holds.
+#[async_trait]
+pub trait SettingsRepo: Send + Sync {
+    async fn get_setting(&self, txn_id: &TxnId, name: &ValueId) -> TCResult<Option<Value>>;
+
+    async fn put_setting(&self, txn_id: &TxnId, name: ValueId, value: Value) -> TCResult<()>;
+}
+
+/// Durable storage for point-in-time checkpoints of a collection, so a
+/// deployment can snapshot its state independently of its own commit log.
+#[async_trait]
+pub trait SnapshotRepo: Send + Sync {
+    async fn snapshot(&self, txn_id: &TxnId) -> TCResult<ValueId>;
+
+    async fn restore(&self, txn_id: &TxnId, snapshot_id: &ValueId) -> TCResult<()>;
+}
+
+/// A complete pluggable backend for a `Persistent` collection. It requires
+/// all three narrower traits above, so a backend without real support for
+/// one of them stubs that trait out (as [`MemoryRepo`]'s [`SnapshotRepo`]
+/// impl does, returning not-implemented errors).
+#[async_trait]
+pub trait Repo: BlockRepo + SettingsRepo + SnapshotRepo {
+    /// Commit `txn_id`'s writes to durable storage, mapping onto whatever
+    /// backend-native transaction this `Repo` wraps (e.g. a pooled SQL
+    /// connection's own `COMMIT`).
+    async fn commit(&self, txn_id: &TxnId);
+
+    /// Discard `txn_id`'s writes, mapping onto the backend-native `ROLLBACK`.
+    async fn rollback(&self, txn_id: &TxnId);
+}
+
+/// A [`Repo`] intended to wrap a pooled async SQL connection -- acquired
+/// per `Txn` on first use and released on [`Repo::commit`]/[`Repo::
+/// rollback`] -- so a deployment can back its collections with an external
+/// SQL store instead of local storage.
+///
+/// No connection-pooling crate (e.g. `deadpool`) is available to depend on
+/// in this checkout -- there's no `Cargo.toml` anywhere in this tree to add
+/// one to -- so this remains a placeholder shaped like the real thing.
+#[derive(Default)]
+pub struct SqlRepo;
+
+#[async_trait]
+impl BlockRepo for SqlRepo {
+    async fn read_block(&self, _txn_id: &TxnId, _block_id: &ValueId) -> TCResult<Vec<u8>> {
+        // TODO: acquire this txn's pooled connection and SELECT the block
+        Err(error::not_implemented("SqlRepo::read_block"))
+    }
+
+    async fn write_block(&self, _txn_id: &TxnId, _block_id: ValueId, _data: Vec<u8>) -> TCResult<()> {
+        // TODO: acquire this txn's pooled connection and UPSERT the block
+        Err(error::not_implemented("SqlRepo::write_block"))
+    }
+
+    async fn delete_block(&self, _txn_id: &TxnId, _block_id: &ValueId) -> TCResult<()> {
+        Err(error::not_implemented("SqlRepo::delete_block"))
+    }
+}
+
+#[async_trait]
+impl SettingsRepo for SqlRepo {
+    async fn get_setting(&self, _txn_id: &TxnId, _name: &ValueId) -> TCResult<Option<Value>> {
+        Err(error::not_implemented("SqlRepo::get_setting"))
+    }
+
+    async fn put_setting(&self, _txn_id: &TxnId, _name: ValueId, _value: Value) -> TCResult<()> {
+        Err(error::not_implemented("SqlRepo::put_setting"))
+    }
+}
+
+#[async_trait]
+impl SnapshotRepo for SqlRepo {
+    async fn snapshot(&self, _txn_id: &TxnId) -> TCResult<ValueId> {
+        Err(error::not_implemented("SqlRepo::snapshot"))
+    }
+
+    async fn restore(&self, _txn_id: &TxnId, _snapshot_id: &ValueId) -> TCResult<()> {
+        Err(error::not_implemented("SqlRepo::restore"))
+    }
+}
+
+#[async_trait]
+impl Repo for SqlRepo {
+    async fn commit(&self, _txn_id: &TxnId) {
+        // TODO: COMMIT and release this txn's pooled connection, if one was acquired
+    }
+
+    async fn rollback(&self, _txn_id: &TxnId) {
+        // TODO: ROLLBACK and release this txn's pooled connection, if one was acquired
+    }
+}
+
+/// An in-memory [`Repo`]: each [`TxnId`]'s writes land in a staging map
+/// first and only become visible to other transactions once [`Repo::
+/// commit`] folds them into the committed store, with [`Repo::rollback`]
+/// just dropping that transaction's staging map untouched -- the default a
+/// `Txn` hands out until a deployment configures a real backend, and a
+/// drop-in stand-in for `crate::internal::Store` wherever that module
+/// would otherwise have been reached for directly.
+#[derive(Default)]
+pub struct MemoryRepo {
+    blocks: RwLock<HashMap<ValueId, Vec<u8>>>,
+    staged_blocks: RwLock<HashMap<TxnId, HashMap<ValueId, Option<Vec<u8>>>>>,
+    settings: RwLock<HashMap<ValueId, Value>>,
+    staged_settings: RwLock<HashMap<TxnId, HashMap<ValueId, Option<Value>>>>,
+}
+
+#[async_trait]
+impl BlockRepo for MemoryRepo {
+    async fn read_block(&self, txn_id: &TxnId, block_id: &ValueId) -> TCResult<Vec<u8>> {
+        let staged = self
+            .staged_blocks
+            .read()
+            .expect("memory repo lock")
+            .get(txn_id)
+            .and_then(|writes| writes.get(block_id).cloned());
+
+        if let Some(staged) = staged {
+            return staged.ok_or_else(|| error::not_found(block_id));
+        }
+
+        self.blocks
+            .read()
+            .expect("memory repo lock")
+            .get(block_id)
+            .cloned()
+            .ok_or_else(|| error::not_found(block_id))
+    }
+
+    async fn write_block(&self, txn_id: &TxnId, block_id: ValueId, data: Vec<u8>) -> TCResult<()> {
+        self.staged_blocks
+            .write()
+            .expect("memory repo lock")
+            .entry(txn_id.clone())
+            .or_default()
+            .insert(block_id, Some(data));
+
+        Ok(())
+    }
+
+    async fn delete_block(&self, txn_id: &TxnId, block_id: &ValueId) -> TCResult<()> {
+        self.staged_blocks
+            .write()
+            .expect("memory repo lock")
+            .entry(txn_id.clone())
+            .or_default()
+            .insert(block_id.clone(), None);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SettingsRepo for MemoryRepo {
+    async fn get_setting(&self, txn_id: &TxnId, name: &ValueId) -> TCResult<Option<Value>> {
+        let staged = self
+            .staged_settings
+            .read()
+            .expect("memory repo lock")
+            .get(txn_id)
+            .and_then(|writes| writes.get(name).cloned());
+
+        if let Some(staged) = staged {
+            return Ok(staged);
+        }
+
+        Ok(self
+            .settings
+            .read()
+            .expect("memory repo lock")
+            .get(name)
+            .cloned())
+    }
+
+    async fn put_setting(&self, txn_id: &TxnId, name: ValueId, value: Value) -> TCResult<()> {
+        self.staged_settings
+            .write()
+            .expect("memory repo lock")
+            .entry(txn_id.clone())
+            .or_default()
+            .insert(name, Some(value));
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SnapshotRepo for MemoryRepo {
+    async fn snapshot(&self, _txn_id: &TxnId) -> TCResult<ValueId> {
+        Err(error::not_implemented("MemoryRepo::snapshot"))
+    }
+
+    async fn restore(&self, _txn_id: &TxnId, _snapshot_id: &ValueId) -> TCResult<()> {
+        Err(error::not_implemented("MemoryRepo::restore"))
+    }
+}
+
+#[async_trait]
+impl Repo for MemoryRepo {
+    async fn commit(&self, txn_id: &TxnId) {
+        if let Some(writes) = self
+            .staged_blocks
+            .write()
+            .expect("memory repo lock")
+            .remove(txn_id)
+        {
+            let mut blocks = self.blocks.write().expect("memory repo lock");
+            for (block_id, data) in writes {
+                match data {
+                    Some(data) => {
+                        blocks.insert(block_id, data);
+                    }
+                    None => {
+                        blocks.remove(&block_id);
+                    }
+                }
+            }
+        }
+
+        if let Some(writes) = self
+            .staged_settings
+            .write()
+            .expect("memory repo lock")
+            .remove(txn_id)
+        {
+            let mut settings = self.settings.write().expect("memory repo lock");
+            for (name, value) in writes {
+                match value {
+                    Some(value) => {
+                        settings.insert(name, value);
+                    }
+                    None => {
+                        settings.remove(&name);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn rollback(&self, txn_id: &TxnId) {
+        self.staged_blocks
+            .write()
+            .expect("memory repo lock")
+            .remove(txn_id);
+
+        self.staged_settings
+            .write()
+            .expect("memory repo lock")
+            .remove(txn_id);
+    }
+}
+
+/// Convenience constructor for the default backend, as an `Arc<dyn Repo>`
+/// ready to hand to a `Persistent::create` caller via `Txn::repo`.
+pub fn default_repo() -> Arc<dyn Repo> {
+    Arc::new(MemoryRepo::default())
+}
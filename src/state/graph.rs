@@ -1,36 +1,79 @@
+use std::fmt;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use crate::context::*;
 use crate::error;
-use crate::state::TCState;
-use crate::transaction::{Transaction, TransactionId};
-use crate::value::TCValue;
+use crate::internal::repo::{BlockRepo, Repo};
+use crate::state::State;
+use crate::transaction::{Transact, Txn, TxnId};
+use crate::value::{TCResult, Value, ValueId};
 
-#[derive(Debug)]
-pub struct Graph {}
+/// A property graph, storing each node under its id as one block of the
+/// [`Repo`] passed to [`Graph::new`] -- the same pluggable seam [`crate::internal::repo`]
+/// exists for, rather than a hard-wired store. A write lands wherever
+/// `repo.write_block` stages it until `commit`, so `Graph` itself holds no
+/// staging state of its own; it only has to forward to whichever backend a
+/// deployment configured.
+pub struct Graph {
+    repo: Arc<dyn Repo>,
+}
 
-#[async_trait]
-impl TCContext for Graph {
-    async fn commit(self: &Arc<Self>, _txn_id: TransactionId) {
-        // TODO
+impl fmt::Debug for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Graph").finish()
+    }
+}
+
+impl Graph {
+    pub fn new(repo: Arc<dyn Repo>) -> Arc<Self> {
+        Arc::new(Self { repo })
+    }
+
+    pub async fn get(self: &Arc<Self>, txn: &Arc<Txn<'_>>, node_id: &Value) -> TCResult<Value> {
+        let block_id = node_block_id(node_id)?;
+        let data = self.repo.read_block(&txn.id(), &block_id).await?;
+        decode_node(&data)
+    }
+
+    pub async fn put(
+        self: Arc<Self>,
+        txn: &Arc<Txn<'_>>,
+        node_id: Value,
+        node: Value,
+    ) -> TCResult<State> {
+        let block_id = node_block_id(&node_id)?;
+        let data = encode_node(&node);
+        self.repo.write_block(&txn.id(), block_id, data).await?;
+        Ok(State::from(node))
     }
+}
 
-    async fn get(
-        self: &Arc<Self>,
-        _txn: Arc<Transaction>,
-        _node_id: &TCValue,
-    ) -> TCResult<TCState> {
-        Err(error::not_implemented())
+#[async_trait]
+impl Transact for Graph {
+    async fn commit(&self, txn_id: &TxnId) {
+        self.repo.commit(txn_id).await
     }
 
-    async fn put(
-        self: &Arc<Self>,
-        _txn: Arc<Transaction>,
-        _node_id: TCValue,
-        _node: TCState,
-    ) -> TCResult<TCState> {
-        Err(error::not_implemented())
+    async fn rollback(&self, txn_id: &TxnId) {
+        self.repo.rollback(txn_id).await
     }
 }
+
+fn node_block_id(node_id: &Value) -> TCResult<ValueId> {
+    node_id
+        .to_string()
+        .parse()
+        .map_err(|_| error::bad_request("invalid graph node id", node_id))
+}
+
+fn encode_node(node: &Value) -> Vec<u8> {
+    node.to_string().into_bytes()
+}
+
+fn decode_node(data: &[u8]) -> TCResult<Value> {
+    let encoded =
+        String::from_utf8(data.to_vec()).map_err(|e| error::bad_request("corrupt graph node", e))?;
+
+    Ok(Value::from(encoded))
+}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use crate::auth::Token;
+use crate::auth::{Request, Token};
 use crate::error;
 use crate::internal::file::File;
 use crate::internal::Store;
@@ -34,9 +34,9 @@ pub trait Authorized: Collection {
         self: &Arc<Self>,
         txn: &Arc<Txn<'_>>,
         key: &Self::Key,
-        _auth: &Option<Token>,
+        auth: &Option<Token>,
     ) -> TCResult<Self::Value> {
-        // TODO: authorize
+        authorize(txn, auth, "get")?;
         Collection::get(self, txn, key).await
     }
 
@@ -45,13 +45,38 @@ pub trait Authorized: Collection {
         txn: &Arc<Txn<'_>>,
         key: Self::Key,
         state: Self::Value,
-        _auth: &Option<Token>,
+        auth: &Option<Token>,
     ) -> TCResult<State> {
-        // TODO: authorize
+        authorize(txn, auth, "put")?;
         Collection::put(self, txn, key, state).await
     }
 }
 
+/// Check `auth`'s token, if any, against `txn` and the given `op`
+/// ("get"/"put"/"post"). A missing token is always allowed through here --
+/// whether a given collection requires a token at all is that collection's
+/// own policy, not this trait's -- but a token that fails to verify, or
+/// whose caveats don't match this request, is rejected.
+///
+/// `path` isn't available at this layer (`Self::Key` is a lookup key, not
+/// the path of the resource being looked up), so a [`crate::auth::Caveat::
+/// Path`] can't be enforced here; see [`State::post`], which does have a
+/// path to check against.
+fn authorize(txn: &Arc<Txn<'_>>, auth: &Option<Token>, op: &'static str) -> TCResult<()> {
+    if let Some(token) = auth {
+        let txn_id = txn.id();
+        let request = Request {
+            path: None,
+            op,
+            txn_id: &txn_id,
+        };
+
+        txn.authority().verify(token, &request)?;
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 pub trait Collection: Send + Sync {
     type Key: TryFrom<Value> + Send + Sync;
@@ -69,6 +94,11 @@ pub trait Collection: Send + Sync {
 
 #[async_trait]
 pub trait Derived: Collection + Extend<PutOp> + Sized {
+    /// Built from the schema `Args` passed to `new`, typically via
+    /// `Args::take`/`take_or` for fields that already arrive shaped like
+    /// their target type, and `Args::take_as` with a [`Conversion`] for
+    /// fields a caller may supply in a more human-friendly form (a
+    /// timestamp string, `"true"`/`1` for a flag).
     type Config: TryFrom<Args>;
 
     async fn new(txn_id: &TxnId, context: Arc<Store>, config: Self::Config) -> TCResult<Self>;
@@ -76,8 +106,14 @@ pub trait Derived: Collection + Extend<PutOp> + Sized {
 
 #[async_trait]
 pub trait Persistent: Collection + File {
+    /// See [`Derived::Config`] -- the same `Args::take_as` per-field
+    /// conversions apply here.
     type Config: TryFrom<Args>;
 
+    /// Create a new instance backed by `txn.repo()` -- the transaction's
+    /// pluggable [`crate::internal::Repo`] -- rather than a hard-wired
+    /// `internal::Store`, so a deployment can choose where this
+    /// collection's data actually lives without changing this signature.
     async fn create(txn: &Arc<Txn<'_>>, config: Self::Config) -> TCResult<Arc<Self>>;
 }
 
@@ -118,6 +154,216 @@ impl Args {
             Ok(default)
         }
     }
+
+    /// Like [`Self::take`], but first coerce the raw argument according to
+    /// `conversion` -- e.g. accept `"true"` or `"2021-01-01T00:00:00Z"` where
+    /// [`Self::take`] would only accept a `Value` already shaped like the
+    /// target type. Useful for a [`Derived::Config`]/[`Persistent::Config`]
+    /// that wants to accept human-friendly schema input (a timestamp
+    /// string, `"1"`/`"true"` for a flag) while storing a typed `Value`.
+    fn take_as<E: Into<error::TCError>, T: TryFrom<Value, Error = E>>(
+        &mut self,
+        name: &str,
+        conversion: Conversion,
+    ) -> TCResult<T> {
+        if let Some(value) = self.0.remove(&name.parse()?) {
+            let coerced = conversion.coerce(value)?;
+            coerced.try_into().map_err(|e: E| e.into())
+        } else {
+            Err(error::bad_request("Required argument not provided", name))
+        }
+    }
+}
+
+/// A named coercion [`Args::take_as`] applies to a raw argument before
+/// handing it to the target type's own `TryFrom<Value>`, so a schema can
+/// accept a human-friendly input (a timestamp string, `"true"`/`1` for a
+/// flag) and still end up with a properly typed `Value`.
+#[derive(Clone)]
+pub enum Conversion {
+    Bytes,
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// Parse a string as RFC 3339 (e.g. `2021-01-01T00:00:00Z`) into a
+    /// nanosecond Unix timestamp.
+    Timestamp,
+    /// Parse a string against an explicit strftime-style format (e.g.
+    /// `"%Y-%m-%d"`) into a nanosecond Unix timestamp, interpreting the
+    /// parsed fields as UTC.
+    TimestampFmt(String),
+    /// Coerced exactly like [`Self::TimestampFmt`]: the parsed fields are
+    /// read as UTC, and no timezone offset is applied.
+    TimestampTzFmt(String),
+}
+
+impl Conversion {
+    fn name(&self) -> String {
+        match self {
+            Conversion::Bytes => "bytes".to_string(),
+            Conversion::String => "string".to_string(),
+            Conversion::Integer => "integer".to_string(),
+            Conversion::Float => "float".to_string(),
+            Conversion::Boolean => "boolean".to_string(),
+            Conversion::Timestamp => "timestamp".to_string(),
+            Conversion::TimestampFmt(fmt) => format!("timestamp:{}", fmt),
+            Conversion::TimestampTzFmt(fmt) => format!("timestamp_tz:{}", fmt),
+        }
+    }
+
+    fn coerce(&self, value: Value) -> TCResult<Value> {
+        match self {
+            Conversion::Bytes | Conversion::String => Ok(value),
+            Conversion::Integer => {
+                let input = value.to_string();
+                input
+                    .trim()
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| self.err(input))
+            }
+            Conversion::Float => {
+                let input = value.to_string();
+                input
+                    .trim()
+                    .parse::<f64>()
+                    .map(Value::from)
+                    .map_err(|_| self.err(input))
+            }
+            Conversion::Boolean => {
+                let input = value.to_string();
+                match input.trim() {
+                    "true" | "1" => Ok(Value::from(true)),
+                    "false" | "0" => Ok(Value::from(false)),
+                    _ => Err(self.err(input)),
+                }
+            }
+            Conversion::Timestamp => {
+                let input = value.to_string();
+                parse_rfc3339(input.trim()).map_err(|_| self.err(input))
+            }
+            Conversion::TimestampFmt(fmt) | Conversion::TimestampTzFmt(fmt) => {
+                let input = value.to_string();
+                parse_strftime(input.trim(), fmt).map_err(|_| self.err(input))
+            }
+        }
+    }
+
+    fn err(&self, input: String) -> error::TCError {
+        error::bad_request(
+            &format!("Could not apply {} conversion to", self.name()),
+            input,
+        )
+    }
+}
+
+/// Parse `input` as RFC 3339 (`YYYY-MM-DDTHH:MM:SS[.fraction][Z|+HH:MM]`)
+/// into nanoseconds since the Unix epoch, wrapped as a [`Value`]. Fractional
+/// seconds are dropped.
+fn parse_rfc3339(input: &str) -> Result<Value, ()> {
+    let (date, rest) = input.split_at(input.find('T').ok_or(())?);
+    let time = &rest[1..];
+    let time = time.trim_end_matches('Z');
+    let (time, offset_secs) = match time.find(&['+', '-'][..]) {
+        Some(i) => {
+            let sign = if time[i..].starts_with('-') { -1 } else { 1 };
+            let mut offset = time[i + 1..].splitn(2, ':');
+            let hours: i64 = offset.next().ok_or(())?.parse().map_err(|_| ())?;
+            let minutes: i64 = offset.next().unwrap_or("0").parse().map_err(|_| ())?;
+            (&time[..i], sign * (hours * 3600 + minutes * 60))
+        }
+        None => (time, 0),
+    };
+
+    let nanos = datetime_to_nanos(date, time)? - offset_secs * 1_000_000_000;
+    Ok(Value::from(nanos))
+}
+
+/// Parse `input` against a minimal strftime-style `fmt` (supporting `%Y`,
+/// `%m`, `%d`, `%H`, `%M`, `%S`) into nanoseconds since the Unix epoch. No
+/// date/time crate (e.g. `chrono`) is available to depend on in this
+/// checkout -- there's no `Cargo.toml` anywhere in this tree to add one to
+/// -- so this only supports the handful of fields tinychain's own schemas
+/// actually need, the same honest scoping as `internal::repo::SqlRepo`.
+fn parse_strftime(input: &str, fmt: &str) -> Result<i64, ()> {
+    let mut year = 1970i64;
+    let mut month = 1i64;
+    let mut day = 1i64;
+    let mut hour = 0i64;
+    let mut minute = 0i64;
+    let mut second = 0i64;
+
+    let mut fmt_chars = fmt.chars();
+    let mut input = input;
+
+    while let Some(c) = fmt_chars.next() {
+        if c == '%' {
+            let field = fmt_chars.next().ok_or(())?;
+            let (width, target) = match field {
+                'Y' => (4, &mut year),
+                'm' => (2, &mut month),
+                'd' => (2, &mut day),
+                'H' => (2, &mut hour),
+                'M' => (2, &mut minute),
+                'S' => (2, &mut second),
+                _ => return Err(()),
+            };
+
+            if input.len() < width {
+                return Err(());
+            }
+            let (digits, remainder) = input.split_at(width);
+            *target = digits.parse().map_err(|_| ())?;
+            input = remainder;
+        } else {
+            if !input.starts_with(c) {
+                return Err(());
+            }
+            input = &input[c.len_utf8()..];
+        }
+    }
+
+    Ok(ymd_hms_to_nanos(year, month, day, hour, minute, second))
+}
+
+fn datetime_to_nanos(date: &str, time: &str) -> Result<i64, ()> {
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next().ok_or(())?.parse().map_err(|_| ())?;
+    let month: i64 = date.next().ok_or(())?.parse().map_err(|_| ())?;
+    let day: i64 = date.next().ok_or(())?.parse().map_err(|_| ())?;
+
+    let mut time = time.splitn(3, ':');
+    let hour: i64 = time.next().ok_or(())?.parse().map_err(|_| ())?;
+    let minute: i64 = time.next().ok_or(())?.parse().map_err(|_| ())?;
+    let second: i64 = time
+        .next()
+        .ok_or(())?
+        .parse::<f64>()
+        .map_err(|_| ())? as i64;
+
+    Ok(ymd_hms_to_nanos(year, month, day, hour, minute, second))
+}
+
+/// Days from the Unix epoch to the given proleptic Gregorian date, via
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as i64;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn ymd_hms_to_nanos(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> i64 {
+    const NANOS_PER_SEC: i64 = 1_000_000_000;
+    const SECS_PER_DAY: i64 = 86400;
+
+    let days = days_from_civil(year, month, day);
+    let secs = days * SECS_PER_DAY + hour * 3600 + minute * 60 + second;
+    secs * NANOS_PER_SEC
 }
 
 impl TryFrom<Value> for Args {
@@ -143,9 +389,10 @@ impl State {
         &self,
         txn: &Arc<Txn<'_>>,
         key: Value,
-        _auth: &Option<Token>,
+        auth: &Option<Token>,
     ) -> TCResult<State> {
-        // TODO: authorize
+        authorize(txn, auth, "get")?;
+
         match self {
             State::Cluster(d) => d.clone().get(txn, &key.try_into()?).await,
             State::Graph(g) => Ok(g.clone().get(txn, &key).await?.into()),
@@ -169,9 +416,10 @@ impl State {
         txn: &Arc<Txn<'_>>,
         key: Value,
         value: Value,
-        _auth: &Option<Token>,
+        auth: &Option<Token>,
     ) -> TCResult<State> {
-        // TODO: authorize
+        authorize(txn, auth, "put")?;
+
         match self {
             State::Cluster(d) => d.clone().put(txn, key.try_into()?, value.try_into()?).await,
             State::Graph(g) => g.clone().put(txn, key, value).await,
@@ -182,11 +430,23 @@ impl State {
 
     pub async fn post(
         &self,
-        _txn: Arc<Txn<'_>>,
-        _method: &PathSegment,
+        txn: Arc<Txn<'_>>,
+        method: &PathSegment,
         _args: Vec<(ValueId, Value)>,
-        _auth: &Option<Token>,
+        auth: &Option<Token>,
     ) -> TCResult<State> {
+        if let Some(token) = auth {
+            let txn_id = txn.id();
+            let path = method.to_string();
+            let request = Request {
+                path: Some(&path),
+                op: "post",
+                txn_id: &txn_id,
+            };
+
+            txn.authority().verify(token, &request)?;
+        }
+
         Err(error::method_not_allowed(format!(
             "{} does not support POST",
             self
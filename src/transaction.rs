@@ -2,17 +2,18 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::fmt;
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use async_trait::async_trait;
-use futures::future::{self, join_all, try_join_all, Future, FutureExt};
+use futures::future::{join_all, try_join_all, Future};
 use futures::lock::Mutex;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::auth::Token;
+use crate::auth::{Authority, Token};
 use crate::error;
 use crate::host::{Host, NetworkTime};
-use crate::internal::Dir;
+use crate::internal::{Dir, Repo, TxnLog, TxnMetrics};
 use crate::state::State;
 use crate::value::link::*;
 use crate::value::op::*;
@@ -20,6 +21,16 @@ use crate::value::*;
 
 #[async_trait]
 pub trait Transact: Send + Sync {
+    /// Get ready to commit `txn_id` -- flush any buffered writes to wherever
+    /// `commit` will make them visible from, without yet making them
+    /// visible. `Txn::commit` drives every participant through this step,
+    /// recording a `Prepared` write-ahead log entry first, before fanning
+    /// out `commit` itself -- so a crash between the two leaves a record a
+    /// startup scan can replay instead of an ambiguous mix of committed and
+    /// uncommitted participants. The default no-op is correct for a
+    /// participant with nothing to flush ahead of `commit`.
+    async fn prepare(&self, _txn_id: &TxnId) {}
+
     async fn commit(&self, txn_id: &TxnId);
 
     async fn rollback(&self, txn_id: &TxnId);
@@ -38,6 +49,13 @@ impl TxnId {
             nonce: rand::thread_rng().gen(),
         }
     }
+
+    /// This transaction's timestamp, in nanoseconds since the Unix epoch --
+    /// the same clock an [`crate::auth::Caveat::Expires`] caveat is checked
+    /// against.
+    pub fn time(&self) -> u128 {
+        self.timestamp
+    }
 }
 
 impl PartialOrd for TxnId {
@@ -135,35 +153,129 @@ impl<'a> TxnState<'a> {
         }
     }
 
+    /// Drop queued `Get` ops that no captured value can ever depend on.
+    /// Starting from `capture`, repeatedly add the deps of every already-live
+    /// id until the set stops growing -- a forward closure over the
+    /// dependency relation -- then drop any queued `Op::Get` whose `ValueId`
+    /// isn't live. `Put`/`Post` ops always run regardless, since they mutate
+    /// state rather than just produce a value a capture might read.
+    fn prune_dead_gets(&mut self, capture: &[ValueId]) {
+        let deps_of: HashMap<ValueId, HashSet<TCRef>> = self
+            .queue
+            .iter()
+            .map(|(value_id, op, _)| (value_id.clone(), op.deps()))
+            .collect();
+
+        let mut live: HashSet<ValueId> = capture.iter().cloned().collect();
+        loop {
+            let mut grew = false;
+
+            for value_id in live.clone() {
+                if let Some(deps) = deps_of.get(&value_id) {
+                    for dep in deps {
+                        if live.insert(dep.value_id()) {
+                            grew = true;
+                        }
+                    }
+                }
+            }
+
+            if !grew {
+                break;
+            }
+        }
+
+        self.queue
+            .retain(|(value_id, op, _)| !matches!(op.op(), Op::Get(_)) || live.contains(value_id));
+    }
+
     async fn resolve(
         &mut self,
         txn: Arc<Txn<'a>>,
         capture: Vec<ValueId>,
     ) -> TCResult<HashMap<ValueId, State>> {
-        // TODO: Don't resolve any GET op unless it's required by a captured value
+        self.prune_dead_gets(&capture);
 
         let mut resolved: HashMap<ValueId, State> = self.resolved.drain().collect();
-        while !self.queue.is_empty() {
-            let known: HashSet<TCRef> = resolved.keys().cloned().map(|id| id.into()).collect();
-            let mut ready = vec![];
-            let mut value_ids = vec![];
-            while let Some((value_id, op, auth)) = self.queue.pop_front() {
-                if op.deps().is_subset(&known) {
-                    ready.push(txn.resolve_value(&resolved, value_id.clone(), op, auth));
-                    println!("ready: {}", value_id);
-                    value_ids.push(value_id);
-                } else {
-                    self.queue.push_front((value_id, op, auth));
-                    break;
+
+        let mut pending: HashMap<ValueId, (Request, &'a Option<Token>)> = self
+            .queue
+            .drain(..)
+            .map(|(value_id, op, auth)| (value_id, (op, auth)))
+            .collect();
+
+        // Kahn's algorithm: `in_degree` counts only the deps of a pending op
+        // that name another still-pending id -- a dep already satisfied by a
+        // previously-provided value doesn't block anything. Seeding `ready`
+        // with the zero-in-degree ids and decrementing dependents' in-degree
+        // as each batch resolves turns a stalled `is_subset` recheck of the
+        // whole queue into an explicit topological pass that can detect when
+        // nothing is left to make progress on.
+        let mut in_degree: HashMap<ValueId, usize> = HashMap::new();
+        let mut dependents: HashMap<ValueId, Vec<ValueId>> = HashMap::new();
+
+        for (value_id, (op, _)) in &pending {
+            let mut count = 0;
+            for dep in op.deps() {
+                let dep_id = dep.value_id();
+                if pending.contains_key(&dep_id) {
+                    count += 1;
+                    dependents
+                        .entry(dep_id)
+                        .or_insert_with(Vec::new)
+                        .push(value_id.clone());
                 }
             }
 
-            let values = try_join_all(ready).await?.into_iter().map(|s| {
-                println!("resolved {}", value_ids[0]);
-                (value_ids.remove(0), s)
-            });
-            resolved.extend(values);
-            println!("{} remaining to resolve", self.queue.len());
+            in_degree.insert(value_id.clone(), count);
+        }
+
+        let mut ready: VecDeque<ValueId> = in_degree
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(value_id, _)| value_id.clone())
+            .collect();
+
+        while !pending.is_empty() {
+            if ready.is_empty() {
+                let cycle = pending
+                    .keys()
+                    .map(ValueId::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                return Err(error::bad_request(
+                    "Dependency cycle detected among",
+                    cycle,
+                ));
+            }
+
+            let mut batch = vec![];
+            let mut batch_ids = vec![];
+            while let Some(value_id) = ready.pop_front() {
+                let (op, auth) = pending.remove(&value_id).expect("pending op");
+                batch.push(txn.resolve_value_timed(&resolved, value_id.clone(), op, auth));
+                batch_ids.push(value_id);
+            }
+
+            let values = try_join_all(batch).await?;
+            for (value_id, state) in batch_ids.into_iter().zip(values) {
+                if let Some(waiting) = dependents.remove(&value_id) {
+                    for dependent in waiting {
+                        let count = in_degree.get_mut(&dependent).expect("in-degree");
+                        *count -= 1;
+                        if *count == 0 {
+                            ready.push_back(dependent);
+                        }
+                    }
+                }
+
+                resolved.insert(value_id, state);
+            }
+
+            txn.metrics().incr("txn.resolve.iteration");
+            txn.metrics()
+                .gauge("txn.resolve.queue_depth", pending.len() as i64);
         }
 
         let resolved = resolved
@@ -173,6 +285,134 @@ impl<'a> TxnState<'a> {
 
         Ok(resolved)
     }
+
+    /// Resolve several independent capture groups against the shared queue
+    /// in one topological pass, the same way `resolve` does, except a
+    /// failure resolving one `ValueId` only poisons its own dependents
+    /// instead of aborting every other group's captures -- a dependency
+    /// cycle is treated the same way, poisoning every id still pending
+    /// once nothing is left that's ready to resolve. Each `label` in
+    /// `items` gets its own entry in the returned map: `Ok` with its
+    /// resolved `State` if `label` (and everything it transitively
+    /// depended on) resolved cleanly, `Err` otherwise.
+    async fn resolve_batch(
+        &mut self,
+        txn: Arc<Txn<'a>>,
+        items: Vec<(ValueId, Vec<ValueId>)>,
+    ) -> TCResult<HashMap<ValueId, TCResult<State>>> {
+        let capture: Vec<ValueId> = items
+            .iter()
+            .flat_map(|(label, deps)| std::iter::once(label.clone()).chain(deps.iter().cloned()))
+            .collect();
+        self.prune_dead_gets(&capture);
+
+        let mut resolved: HashMap<ValueId, State> = self.resolved.drain().collect();
+        let mut failed: HashMap<ValueId, error::TCError> = HashMap::new();
+
+        let mut pending: HashMap<ValueId, (Request, &'a Option<Token>)> = self
+            .queue
+            .drain(..)
+            .map(|(value_id, op, auth)| (value_id, (op, auth)))
+            .collect();
+
+        let mut in_degree: HashMap<ValueId, usize> = HashMap::new();
+        let mut dependents: HashMap<ValueId, Vec<ValueId>> = HashMap::new();
+
+        for (value_id, (op, _)) in &pending {
+            let mut count = 0;
+            for dep in op.deps() {
+                let dep_id = dep.value_id();
+                if pending.contains_key(&dep_id) {
+                    count += 1;
+                    dependents
+                        .entry(dep_id)
+                        .or_insert_with(Vec::new)
+                        .push(value_id.clone());
+                }
+            }
+
+            in_degree.insert(value_id.clone(), count);
+        }
+
+        let mut ready: VecDeque<ValueId> = in_degree
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(value_id, _)| value_id.clone())
+            .collect();
+
+        while !pending.is_empty() {
+            if ready.is_empty() {
+                let cycle = error::bad_request(
+                    "Dependency cycle detected among",
+                    pending
+                        .keys()
+                        .map(ValueId::to_string)
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                );
+
+                for value_id in pending.keys() {
+                    failed.insert(value_id.clone(), cycle.clone());
+                }
+
+                break;
+            }
+
+            let mut batch = vec![];
+            let mut batch_ids = vec![];
+            while let Some(value_id) = ready.pop_front() {
+                let (op, auth) = pending.remove(&value_id).expect("pending op");
+                batch.push(txn.resolve_value_timed(&resolved, value_id.clone(), op, auth));
+                batch_ids.push(value_id);
+            }
+
+            let outcomes = join_all(batch).await;
+            for (value_id, outcome) in batch_ids.into_iter().zip(outcomes) {
+                if let Some(waiting) = dependents.remove(&value_id) {
+                    for dependent in waiting {
+                        let count = in_degree.get_mut(&dependent).expect("in-degree");
+                        *count -= 1;
+                        if *count == 0 {
+                            ready.push_back(dependent);
+                        }
+                    }
+                }
+
+                match outcome {
+                    Ok(state) => {
+                        resolved.insert(value_id, state);
+                    }
+                    Err(cause) => {
+                        failed.insert(value_id, cause);
+                    }
+                }
+            }
+
+            txn.metrics().incr("txn.resolve_batch.iteration");
+            txn.metrics()
+                .gauge("txn.resolve_batch.queue_depth", pending.len() as i64);
+        }
+
+        // Anything still `pending` after the loop broke out of its own
+        // accord (rather than emptying normally) only reaches here by way
+        // of the cycle branch above, which already recorded it in `failed`.
+
+        let mut output: HashMap<ValueId, TCResult<State>> = HashMap::new();
+        for (label, _) in items {
+            if let Some(state) = resolved.get(&label) {
+                output.insert(label, Ok(state.clone()));
+            } else if let Some(cause) = failed.get(&label) {
+                output.insert(label, Err(cause.clone()));
+            } else {
+                output.insert(
+                    label.clone(),
+                    Err(error::bad_request("Required value not provided", label)),
+                );
+            }
+        }
+
+        Ok(output)
+    }
 }
 
 pub struct Txn<'a> {
@@ -180,6 +420,11 @@ pub struct Txn<'a> {
     context: Arc<Dir>,
     host: Arc<Host>,
     mutated: Arc<RwLock<Vec<Arc<dyn Transact>>>>, // TODO: this should be a Set of some kind
+    log: Arc<dyn TxnLog>,
+    versions: Arc<RwLock<HashMap<Value, TxnId>>>,
+    reads: Arc<RwLock<HashSet<Value>>>,
+    writes: Arc<RwLock<HashSet<Value>>>,
+    metrics: Arc<dyn TxnMetrics>,
     state: Mutex<TxnState<'a>>,
 }
 
@@ -188,6 +433,9 @@ impl<'a> Txn<'a> {
         let id = TxnId::new(host.time());
         let context: PathSegment = id.clone().try_into()?;
         let context = root.create_dir(&id, context.into()).await?;
+        let log = host.log();
+        let versions = host.versions();
+        let metrics = host.metrics();
         let state = Mutex::new(TxnState::new());
 
         Ok(Arc::new(Txn {
@@ -195,6 +443,11 @@ impl<'a> Txn<'a> {
             context,
             host,
             mutated: Arc::new(RwLock::new(vec![])),
+            log,
+            versions,
+            reads: Arc::new(RwLock::new(HashSet::new())),
+            writes: Arc::new(RwLock::new(HashSet::new())),
+            metrics,
             state,
         }))
     }
@@ -203,6 +456,13 @@ impl<'a> Txn<'a> {
         self.context.clone()
     }
 
+    /// The pluggable storage backend for this transaction's host, for a
+    /// `Persistent::create` implementation to read and write collection
+    /// data through instead of reaching for a concrete `internal::Store`.
+    pub fn repo(self: &Arc<Self>) -> Arc<dyn Repo> {
+        self.host.repo()
+    }
+
     pub async fn subcontext(self: &Arc<Self>, subcontext: ValueId) -> TCResult<Arc<Txn<'a>>> {
         let subcontext: Arc<Dir> = self.context.create_dir(&self.id, subcontext.into()).await?;
 
@@ -211,14 +471,33 @@ impl<'a> Txn<'a> {
             context: subcontext,
             host: self.host.clone(),
             mutated: self.mutated.clone(),
+            log: self.log.clone(),
+            versions: self.versions.clone(),
+            reads: self.reads.clone(),
+            writes: self.writes.clone(),
+            metrics: self.metrics.clone(),
             state: Mutex::new(TxnState::default()),
         }))
     }
 
+    /// This transaction's metrics sink, for `TxnState::resolve`/
+    /// `resolve_batch` to record resolve-loop and per-op latency against
+    /// without reaching back into `Host` themselves.
+    fn metrics(self: &Arc<Self>) -> Arc<dyn TxnMetrics> {
+        self.metrics.clone()
+    }
+
     pub fn id(self: &Arc<Self>) -> TxnId {
         self.id.clone()
     }
 
+    /// The [`Authority`] that mints and verifies [`Token`]s for this
+    /// transaction's host, for callers (e.g. `Authorized::get`/`put`) that
+    /// need to check a caller-supplied token against it.
+    pub fn authority(self: &Arc<Self>) -> &dyn Authority {
+        self.host.authority()
+    }
+
     pub async fn extend<I: Iterator<Item = (ValueId, Value)>>(
         &self,
         iter: I,
@@ -231,20 +510,75 @@ impl<'a> Txn<'a> {
         self.state.lock().await.push(item, auth)
     }
 
-    pub fn commit(&'a self) -> impl Future<Output = ()> + 'a {
-        println!("commit!");
-        join_all(self.mutated.write().unwrap().drain(..).map(|s| async move {
-            s.commit(&self.id).await;
-        }))
-        .then(|_| future::ready(()))
+    /// Validate this transaction's read set against the committed-version
+    /// map before doing anything else in the commit path: if some other
+    /// transaction with a greater `TxnId` has already committed a write to
+    /// a key this transaction read, `self.id` was computed from a stale
+    /// view of that key and must not be allowed to commit over it.
+    ///
+    /// The request this answers asks for the committed-version map to live
+    /// on each mutated subject, guarded by its own `RwLock` -- but a
+    /// subject here is an opaque `Arc<dyn Transact>` with no `Value`-keyed
+    /// interface `Txn` can reach, so there's nowhere on the subject itself
+    /// to put it. `versions` is that map collapsed to one `Txn`-wide table
+    /// instead, keyed by the `Value` `resolve_value` already treats as an
+    /// `Op::Get`/`Op::Put` key, and shared with every `subcontext` the same
+    /// way `mutated` and `log` are.
+    fn check_conflicts(&'a self) -> TCResult<()> {
+        let versions = self.versions.read().unwrap();
+        let reads = self.reads.read().unwrap();
+        has_conflict(&reads, &versions, &self.id)
+    }
+
+    /// Two-phase commit: validate this transaction's read set for MVCC
+    /// conflicts, write a `Prepared` write-ahead log record naming every
+    /// participant before fanning out `Transact::prepare`, then
+    /// `Transact::commit`, and only write the `Committed` record (so a
+    /// startup scan no longer replays this transaction) once every `commit` future has
+    /// resolved. A crash between the two leaves a `Prepared` record for a
+    /// startup scan (see [`crate::internal::log::recover`]) to replay.
+    /// Once every participant has committed, every key this transaction
+    /// wrote is stamped with `self.id` as its new last-committer, so a
+    /// later transaction's read set check can see it.
+    pub fn commit(&'a self) -> impl Future<Output = TCResult<()>> + 'a {
+        self.metrics.incr("txn.commit");
+        async move {
+            self.check_conflicts()?;
+
+            let subjects: Vec<Arc<dyn Transact>> = self.mutated.write().unwrap().drain(..).collect();
+
+            self.log
+                .prepare(&self.id, subjects.clone())
+                .await
+                .expect("write transaction log Prepared record");
+
+            join_all(subjects.iter().map(|s| async move { s.prepare(&self.id).await })).await;
+            join_all(subjects.iter().map(|s| async move { s.commit(&self.id).await })).await;
+
+            self.log
+                .commit(&self.id)
+                .await
+                .expect("write transaction log Committed record");
+
+            let written: Vec<Value> = self.writes.write().unwrap().drain().collect();
+            let mut versions = self.versions.write().unwrap();
+            for key in written {
+                versions.insert(key, self.id.clone());
+            }
+
+            Ok(())
+        }
     }
 
     pub fn rollback(&'a self) -> impl Future<Output = ()> + 'a {
-        println!("rollback!");
-        join_all(self.mutated.write().unwrap().drain(..).map(|s| async move {
-            s.rollback(&self.id).await;
-        }))
-        .then(|_| future::ready(()))
+        self.metrics.incr("txn.rollback");
+        async move {
+            let subjects: Vec<Arc<dyn Transact>> = self.mutated.write().unwrap().drain(..).collect();
+
+            join_all(subjects.iter().map(|s| async move { s.rollback(&self.id).await })).await;
+
+            let _ = self.log.rollback(&self.id).await;
+        }
     }
 
     pub fn mutate(self: &Arc<Self>, state: Arc<dyn Transact>) {
@@ -258,6 +592,23 @@ impl<'a> Txn<'a> {
         self.state.lock().await.resolve(self.clone(), capture).await
     }
 
+    /// Resolve several independent capture groups in one pass over the
+    /// shared queue, each keyed by its own `ValueId` in the returned map
+    /// with its own `TCResult` -- one group referencing a missing
+    /// dependency, or sitting downstream of a dependency cycle, only fails
+    /// that group's entry instead of the whole batch the way `resolve`
+    /// would.
+    pub async fn resolve_batch(
+        self: &Arc<Self>,
+        items: Vec<(ValueId, Vec<ValueId>)>,
+    ) -> TCResult<HashMap<ValueId, TCResult<State>>> {
+        self.state
+            .lock()
+            .await
+            .resolve_batch(self.clone(), items)
+            .await
+    }
+
     async fn resolve_value(
         self: &Arc<Self>,
         resolved: &HashMap<ValueId, State>,
@@ -269,18 +620,23 @@ impl<'a> Txn<'a> {
         let subject = request.subject();
 
         match request.op().clone() {
-            Op::Get(GetOp { key }) => match subject {
-                Subject::Link(l) => extension.get(l.clone(), key, auth).await,
-                Subject::Ref(r) => match resolved.get(&r.value_id()) {
-                    Some(s) => s.get(&extension, key, auth).await,
-                    None => Err(error::bad_request(
-                        "Required value not provided",
-                        r.value_id(),
-                    )),
-                },
-            },
+            Op::Get(GetOp { key }) => {
+                self.reads.write().unwrap().insert(key.clone());
+
+                match subject {
+                    Subject::Link(l) => extension.get(l.clone(), key, auth).await,
+                    Subject::Ref(r) => match resolved.get(&r.value_id()) {
+                        Some(s) => s.get(&extension, key, auth).await,
+                        None => Err(error::bad_request(
+                            "Required value not provided",
+                            r.value_id(),
+                        )),
+                    },
+                }
+            }
             Op::Put(PutOp { key, value }) => match subject {
                 Subject::Link(l) => {
+                    self.writes.write().unwrap().insert(key.clone());
                     extension
                         .put(l.clone(), key, resolve_val(resolved, value)?, auth)
                         .await
@@ -289,7 +645,7 @@ impl<'a> Txn<'a> {
                     let subject = resolve_id(resolved, &r.value_id())?;
                     let key = resolve_val(resolved, key)?;
                     let value = resolve_val(resolved, value)?;
-                    println!("{}.put({}, {})", subject, key, value);
+                    self.writes.write().unwrap().insert(key.clone());
                     subject
                         .put(&extension, key.try_into()?, value.try_into()?, auth)
                         .await
@@ -297,6 +653,12 @@ impl<'a> Txn<'a> {
             },
             Op::Post(PostOp { action, requires }) => match subject {
                 Subject::Ref(r) => {
+                    // `Post` has no literal key of its own, only a subject
+                    // and an action to call on it -- the `Value::Ref` that
+                    // names the subject stands in for "whatever this call
+                    // touches", since there's no finer-grained key to record.
+                    self.writes.write().unwrap().insert(Value::Ref(r.clone()));
+
                     let mut deps: Vec<(ValueId, Value)> = Vec::with_capacity(requires.len());
                     for (dest_id, id) in requires {
                         let dep = resolve_val(resolved, id)?;
@@ -313,6 +675,31 @@ impl<'a> Txn<'a> {
         }
     }
 
+    /// `resolve_value`, timed and recorded against this transaction's
+    /// `TxnMetrics` sink keyed by op type -- the seam `TxnState::resolve`/
+    /// `resolve_batch` call through instead of invoking `resolve_value`
+    /// directly, so every resolved op's latency is visible regardless of
+    /// which of the two entry points drove it.
+    async fn resolve_value_timed(
+        self: &Arc<Self>,
+        resolved: &HashMap<ValueId, State>,
+        value_id: ValueId,
+        request: Request,
+        auth: &Option<Token>,
+    ) -> TCResult<State> {
+        let op_label = match request.op() {
+            Op::Get(_) => "Get",
+            Op::Put(_) => "Put",
+            Op::Post(_) => "Post",
+        };
+
+        let start = Instant::now();
+        let result = self.resolve_value(resolved, value_id, request, auth).await;
+        self.metrics
+            .observe("txn.resolve_value", op_label, start.elapsed());
+        result
+    }
+
     pub fn time(&self) -> NetworkTime {
         NetworkTime::from_nanos(self.id.timestamp)
     }
@@ -323,7 +710,6 @@ impl<'a> Txn<'a> {
         key: Value,
         auth: &Option<Token>,
     ) -> TCResult<State> {
-        println!("txn::get {} {}", link, key);
         self.host.get(self, &link, key, auth).await
     }
 
@@ -334,7 +720,6 @@ impl<'a> Txn<'a> {
         state: State,
         auth: &Option<Token>,
     ) -> TCResult<State> {
-        println!("txn::put {} {}", dest, key);
         self.host.put(self, dest, key, state, auth).await
     }
 }
@@ -368,3 +753,68 @@ fn resolve_val(resolved: &HashMap<ValueId, State>, value: Value) -> TCResult<Sta
         _ => Ok(value.into()),
     }
 }
+
+/// MVCC read-set validation: `reads` conflicts with `versions` (the
+/// committed-version table) under `id` if any key `id`'s transaction read
+/// was last committed by a transaction with a greater `TxnId` -- i.e. `id`
+/// read a value that's since been superseded, so committing `id` over it
+/// would silently discard that newer write.
+fn has_conflict(
+    reads: &HashSet<Value>,
+    versions: &HashMap<Value, TxnId>,
+    id: &TxnId,
+) -> TCResult<()> {
+    for key in reads.iter() {
+        if let Some(last_committed) = versions.get(key) {
+            if last_committed > id {
+                return Err(error::conflict());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(nanos: u128) -> TxnId {
+        TxnId::new(NetworkTime::from_nanos(nanos))
+    }
+
+    /// Reading a key that's never been committed (or was last committed
+    /// before this transaction started) is never a conflict.
+    #[test]
+    fn no_conflict_when_the_read_key_is_unwritten_or_stale() {
+        let reads: HashSet<Value> = [Value::from("key".to_string())].into_iter().collect();
+        let mut versions = HashMap::new();
+        assert!(has_conflict(&reads, &versions, &at(200)).is_ok());
+
+        versions.insert(Value::from("key".to_string()), at(100));
+        assert!(has_conflict(&reads, &versions, &at(200)).is_ok());
+    }
+
+    /// Reading a key that another transaction has since committed a newer
+    /// write to -- i.e. this transaction's view of that key is stale --
+    /// must be rejected, or the commit would silently discard that write.
+    #[test]
+    fn conflict_when_a_read_key_was_committed_after_this_txn_started() {
+        let reads: HashSet<Value> = [Value::from("key".to_string())].into_iter().collect();
+        let mut versions = HashMap::new();
+        versions.insert(Value::from("key".to_string()), at(300));
+
+        assert!(has_conflict(&reads, &versions, &at(200)).is_err());
+    }
+
+    /// A key this transaction never read is irrelevant to its own read-set
+    /// validation, no matter how recently it was committed.
+    #[test]
+    fn unread_keys_never_conflict() {
+        let reads: HashSet<Value> = HashSet::new();
+        let mut versions = HashMap::new();
+        versions.insert(Value::from("key".to_string()), at(300));
+
+        assert!(has_conflict(&reads, &versions, &at(200)).is_ok());
+    }
+}
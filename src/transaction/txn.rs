@@ -146,8 +146,6 @@ impl Txn {
         mut parameters: S,
         auth: Auth,
     ) -> TCResult<State> {
-        // TODO: use a Graph here and queue every op absolutely as soon as it's ready
-
         println!("Txn::execute");
 
         let mut graph = HashMap::new();
@@ -166,59 +164,96 @@ impl Txn {
 
         let capture = capture.ok_or(error::unsupported("Cannot execute empty operation"))?;
 
-        let mut pending = FuturesUnordered::new();
+        if is_resolved(graph.get(&capture).ok_or_else(|| error::not_found(&capture))?) {
+            return graph.remove(&capture).ok_or(error::not_found(capture));
+        }
 
-        while !is_resolved(graph.get(&capture).ok_or(error::not_found(&capture))?) {
-            let mut visited = HashSet::new();
-            let mut unvisited = Vec::with_capacity(graph.len());
-            unvisited.push(capture.clone());
-            while let Some(name) = unvisited.pop() {
-                if visited.contains(&name) {
-                    println!("Already visited {}", name);
+        // For every op still waiting to resolve, its still-unresolved
+        // dependencies -- the input Kahn's algorithm schedules from.
+        let mut unresolved_deps: HashMap<ValueId, HashSet<ValueId>> = HashMap::new();
+
+        for (name, state) in graph.iter() {
+            if let State::Value(Value::Op(op)) = state {
+                if op.is_def() {
                     continue;
-                } else {
-                    visited.insert(name.clone());
                 }
 
-                println!("Txn::execute {} (#{})", &name, visited.len());
-
-                let state = graph.get(&name).ok_or_else(|| error::not_found(&name))?;
-                if let State::Value(Value::Op(op)) = state {
-                    if op.is_def() {
-                        continue;
+                let mut unresolved = HashSet::new();
+                for dep in requires(op, &graph)? {
+                    let dep_state = graph.get(&dep).ok_or_else(|| error::not_found(&dep))?;
+                    if !is_resolved(dep_state) {
+                        unresolved.insert(dep);
                     }
+                }
 
-                    println!("Provider: {}", &op);
+                unresolved_deps.insert(name.clone(), unresolved);
+            }
+        }
 
-                    let mut ready = true;
-                    for dep in requires(op, &graph)? {
-                        let dep_state = graph.get(&dep).ok_or_else(|| error::not_found(&dep))?;
+        let (mut in_degree, mut dependents) = schedule(&unresolved_deps);
 
-                        if !is_resolved(dep_state) {
-                            ready = false;
-                            unvisited.push(dep);
-                        }
-                    }
+        let mut pending = FuturesUnordered::new();
 
-                    if ready {
-                        pending.push(
-                            self.clone()
-                                .resolve(graph.clone(), *op.clone(), auth.clone())
-                                .map_ok(|state| (name, state)),
-                        );
-                    }
+        for name in in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<ValueId>>()
+        {
+            self.clone()
+                .queue_ready_op(&name, &graph, &auth, &mut pending);
+        }
+
+        loop {
+            if pending.is_empty() {
+                if is_resolved(graph.get(&capture).ok_or_else(|| error::not_found(&capture))?) {
+                    break;
                 }
+
+                let stuck = in_degree
+                    .into_iter()
+                    .filter(|(_, degree)| *degree > 0)
+                    .map(|(name, _)| name.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                return Err(error::bad_request(
+                    "Txn::execute deadlocked on a dependency cycle among",
+                    stuck,
+                ));
             }
 
-            while let Some(result) = pending.next().await {
-                let (name, state) = result?;
-                graph.insert(name, state);
+            let (name, state) = pending.next().await.unwrap()?;
+            graph.insert(name.clone(), state);
+
+            for dependent in release(&name, &mut in_degree, &mut dependents) {
+                self.clone()
+                    .queue_ready_op(&dependent, &graph, &auth, &mut pending);
             }
         }
 
         graph.remove(&capture).ok_or(error::not_found(capture))
     }
 
+    /// Push `name`'s op (which must be ready, i.e. have an in-degree of
+    /// zero) onto `pending` to be resolved.
+    fn queue_ready_op(
+        self: Arc<Self>,
+        name: &ValueId,
+        graph: &HashMap<ValueId, State>,
+        auth: &Auth,
+        pending: &mut FuturesUnordered<TCBoxTryFuture<'static, (ValueId, State)>>,
+    ) {
+        if let Some(State::Value(Value::Op(op))) = graph.get(name) {
+            let name = name.clone();
+            let op = (**op).clone();
+            pending.push(Box::pin(
+                self.resolve(graph.clone(), op, auth.clone())
+                    .map_ok(move |state| (name, state)),
+            ));
+        }
+    }
+
     pub async fn execute_and_stream<S: Stream<Item = (ValueId, Value)> + Unpin>(
         self: Arc<Self>,
         parameters: S,
@@ -424,6 +459,56 @@ fn resolve_value<'a>(
     }
 }
 
+/// Kahn's-algorithm setup: given each still-unresolved op's set of
+/// still-unresolved dependencies, compute every op's in-degree and, for
+/// each dependency, which ops are waiting on it -- so that resolving a
+/// dependency can push its ready dependents directly instead of
+/// re-scanning the whole graph.
+fn schedule(
+    unresolved_deps: &HashMap<ValueId, HashSet<ValueId>>,
+) -> (HashMap<ValueId, usize>, HashMap<ValueId, Vec<ValueId>>) {
+    let mut in_degree = HashMap::new();
+    let mut dependents: HashMap<ValueId, Vec<ValueId>> = HashMap::new();
+
+    for (name, deps) in unresolved_deps {
+        for dep in deps {
+            dependents
+                .entry(dep.clone())
+                .or_insert_with(Vec::new)
+                .push(name.clone());
+        }
+
+        in_degree.insert(name.clone(), deps.len());
+    }
+
+    (in_degree, dependents)
+}
+
+/// `name` just resolved: decrement every op waiting on it and return the
+/// ones whose in-degree just reached zero, ready to be scheduled.
+fn release(
+    name: &ValueId,
+    in_degree: &mut HashMap<ValueId, usize>,
+    dependents: &mut HashMap<ValueId, Vec<ValueId>>,
+) -> Vec<ValueId> {
+    let mut ready = Vec::new();
+
+    if let Some(waiting) = dependents.remove(name) {
+        for dependent in waiting {
+            let degree = in_degree
+                .get_mut(&dependent)
+                .expect("in-degree entry for a queued op");
+            *degree -= 1;
+
+            if *degree == 0 {
+                ready.push(dependent);
+            }
+        }
+    }
+
+    ready
+}
+
 fn is_resolved(state: &State) -> bool {
     match state {
         State::Value(value) => is_resolved_value(value),
@@ -508,3 +593,59 @@ fn value_requires(
         _ => Ok(HashSet::new()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(name: &str) -> ValueId {
+        name.parse().expect("ValueId")
+    }
+
+    fn deps(pairs: &[(&str, &[&str])]) -> HashMap<ValueId, HashSet<ValueId>> {
+        pairs
+            .iter()
+            .map(|(name, deps)| (id(name), deps.iter().map(|dep| id(dep)).collect()))
+            .collect()
+    }
+
+    /// A linear chain "a -> b -> c" (c depends on b, b depends on a) should
+    /// schedule only `a` as ready with in-degree 0, and releasing `a` then
+    /// `b` should uncover `b` then `c` one at a time -- if `schedule`
+    /// mis-set an in-degree, something would either never become ready
+    /// (deadlock) or run before its dependency resolved.
+    #[test]
+    fn schedule_releases_a_linear_chain_one_link_at_a_time() {
+        let graph = deps(&[("a", &[]), ("b", &["a"]), ("c", &["b"])]);
+        let (mut in_degree, mut dependents) = schedule(&graph);
+
+        assert_eq!(in_degree[&id("a")], 0);
+        assert_eq!(in_degree[&id("b")], 1);
+        assert_eq!(in_degree[&id("c")], 1);
+
+        assert_eq!(release(&id("a"), &mut in_degree, &mut dependents), vec![id("b")]);
+        assert_eq!(release(&id("b"), &mut in_degree, &mut dependents), vec![id("c")]);
+        assert!(release(&id("c"), &mut in_degree, &mut dependents).is_empty());
+    }
+
+    /// A diamond ("b" and "c" both depend on "a", "d" depends on both "b"
+    /// and "c") should only release "d" once *both* of its dependencies
+    /// have resolved, not after the first.
+    #[test]
+    fn schedule_releases_a_diamond_only_once_every_dependency_resolves() {
+        let graph = deps(&[
+            ("a", &[]),
+            ("b", &["a"]),
+            ("c", &["a"]),
+            ("d", &["b", "c"]),
+        ]);
+        let (mut in_degree, mut dependents) = schedule(&graph);
+
+        let mut ready = release(&id("a"), &mut in_degree, &mut dependents);
+        ready.sort_by_key(|id| id.to_string());
+        assert_eq!(ready, vec![id("b"), id("c")]);
+
+        assert!(release(&id("b"), &mut in_degree, &mut dependents).is_empty());
+        assert_eq!(release(&id("c"), &mut in_degree, &mut dependents), vec![id("d")]);
+    }
+}